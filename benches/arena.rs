@@ -0,0 +1,61 @@
+//! Compare reading many boxed vals keeping their values inline with vals keeping them in a
+//! `ValueArena`.
+//!
+//! Run with `cargo bench --bench arena`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use lazy_catch::var::Var;
+use lazy_catch::{System, SystemNode, Update};
+
+const NODES: usize = 10_000;
+const ROUNDS: u32 = 200;
+
+/// A closure reading one var, so that every node recomputes after a modify of it.
+fn update(x: &Var<u64>, i: u64) -> impl FnMut(Update<[u64; 4]>) + '_ {
+    move |mut u: Update<[u64; 4]>| {
+        let x = *u.get(x);
+        u.update(|| [x, x + i, x * i, x ^ i]);
+    }
+}
+
+/// Modify `x` and read every node, `ROUNDS` times.
+fn measure(
+    system: &mut System,
+    x: &Var<u64>,
+    nodes: &[Box<dyn SystemNode<Value = [u64; 4]> + '_>],
+) -> Duration {
+    let read_all = |system: &System| nodes.iter().map(|n| system.get(&**n)[3]).sum::<u64>();
+    read_all(system);
+    let start = Instant::now();
+    for round in 0..ROUNDS {
+        system.set(x, u64::from(round));
+        black_box(read_all(system));
+    }
+    start.elapsed()
+}
+
+fn report(name: &str, elapsed: Duration) {
+    println!(
+        "{name:>6}: {:?} per modify and read of {NODES} nodes",
+        elapsed / ROUNDS
+    );
+}
+
+fn main() {
+    let mut system = System::new();
+    let x = system.var(0);
+
+    let inline: Vec<Box<dyn SystemNode<Value = [u64; 4]>>> = (0..NODES as u64)
+        .map(|i| Box::new(system.val(update(&x, i))) as _)
+        .collect();
+    report("inline", measure(&mut system, &x, &inline));
+    drop(inline);
+
+    let arena = system.with_arena();
+    let pooled: Vec<Box<dyn SystemNode<Value = [u64; 4]>>> = (0..NODES as u64)
+        .map(|i| Box::new(arena.val(&system, update(&x, i))) as _)
+        .collect();
+    report("arena", measure(&mut system, &x, &pooled));
+}
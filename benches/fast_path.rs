@@ -0,0 +1,90 @@
+//! Compare reading many vals after a modify of a var none of them reads, with and without
+//! the dependency check of `FastVal`.
+//!
+//! Run with `cargo bench --bench fast_path`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use lazy_catch::fast::FastDeps;
+use lazy_catch::var::Var;
+use lazy_catch::{System, SystemNode, Update};
+
+const NODES: usize = 1_000;
+const ROUNDS: u32 = 200;
+
+/// Mix `acc` `work` times, the part a fast path skips.
+fn mix(mut acc: u64, work: u32) -> u64 {
+    for _ in 0..work {
+        acc = black_box(acc.wrapping_mul(31).rotate_left(7));
+    }
+    acc
+}
+
+/// A closure reading four deps and mixing their values.
+fn update<'n>(xs: &'n [Var<u64>], i: usize, work: u32) -> impl FnMut(Update<u64>) + 'n {
+    move |mut u: Update<u64>| {
+        let acc = xs[i..i + 4]
+            .iter()
+            .fold(0u64, |acc, x| acc.wrapping_add(*u.get(x)));
+        let acc = mix(acc, work);
+        u.update(|| acc);
+    }
+}
+
+/// The same closure for a `FastVal`, reading the deps through `FastDeps`.
+fn fast_update<'n>(
+    xs: &'n [Var<u64>],
+    i: usize,
+    work: u32,
+) -> impl FnMut(&mut FastDeps<'n>, Update<u64>) + 'n {
+    move |deps: &mut FastDeps<'n>, mut u: Update<u64>| {
+        let acc = xs[i..i + 4]
+            .iter()
+            .fold(0u64, |acc, x| acc.wrapping_add(*deps.get(&mut u, x)));
+        let acc = mix(acc, work);
+        u.update(|| acc);
+    }
+}
+
+/// Modify `unrelated` and read every node, `ROUNDS` times.
+fn measure<N: SystemNode<Value = u64>>(
+    system: &mut System,
+    unrelated: &Var<u64>,
+    nodes: &[N],
+) -> Duration {
+    let read_all = |system: &System| nodes.iter().map(|n| *system.get(n)).sum::<u64>();
+    read_all(system);
+    let start = Instant::now();
+    for round in 0..ROUNDS {
+        system.set(unrelated, u64::from(round));
+        black_box(read_all(system));
+    }
+    start.elapsed()
+}
+
+fn report(name: &str, work: u32, elapsed: Duration) {
+    println!(
+        "{name:>8}, work {work:>3}: {:?} per modify and read of {NODES} nodes",
+        elapsed / ROUNDS
+    );
+}
+
+fn main() {
+    let mut system = System::new();
+    let unrelated = system.var(0);
+    let xs: Vec<_> = (0..NODES as u64 + 4).map(|x| system.var(x)).collect();
+
+    for work in [0, 16, 256] {
+        let vals: Vec<_> = (0..NODES)
+            .map(|i| system.val(update(&xs, i, work)))
+            .collect();
+        report("val", work, measure(&mut system, &unrelated, &vals));
+        drop(vals);
+
+        let fast: Vec<_> = (0..NODES)
+            .map(|i| system.fast_val(fast_update(&xs, i, work)))
+            .collect();
+        report("fast_val", work, measure(&mut system, &unrelated, &fast));
+    }
+}
@@ -0,0 +1,55 @@
+//! Compare `System::reduce` with a val summing the same vars by hand.
+//!
+//! Run with `cargo bench --bench reduce`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use lazy_catch::var::Var;
+use lazy_catch::{System, Update};
+
+const NODES: i64 = 1_000;
+const ROUNDS: u32 = 1_000;
+
+fn vars(system: &System) -> Vec<Var<i64>> {
+    (0..NODES).map(|x| system.var(x)).collect()
+}
+
+/// Modify one var and read `sum`, `ROUNDS` times.
+fn measure(system: &mut System, xs: &[Var<i64>], read: impl Fn(&System) -> i64) -> Duration {
+    read(system);
+    let start = Instant::now();
+    for round in 0..ROUNDS {
+        let x = &xs[round as usize % xs.len()];
+        system.set(x, i64::from(round));
+        black_box(read(system));
+    }
+    start.elapsed()
+}
+
+fn report(name: &str, elapsed: Duration) {
+    println!("{name:>8}: {:?} per modify and read", elapsed / ROUNDS);
+}
+
+fn main() {
+    let mut system = System::new();
+    let xs = vars(&system);
+    let sum = system.reduce(&xs, || 0, |sum, x| sum + x);
+    report("reduce", measure(&mut system, &xs, |s| *s.get(&sum)));
+
+    let mut system = System::new();
+    let xs = vars(&system);
+    let sum = system.val(|mut u: Update<i64>| {
+        let mut sum = 0;
+        for x in &xs {
+            sum += *u.get(x);
+        }
+        u.update(|| sum);
+    });
+    report("manual", measure(&mut system, &xs, |s| *s.get(&sum)));
+
+    let mut system = System::new();
+    let xs = vars(&system);
+    let plain = |s: &System| xs.iter().map(|x| *s.get(x)).sum();
+    report("no val", measure(&mut system, &xs, plain));
+}
@@ -0,0 +1,28 @@
+#[cfg(feature = "access")]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::SystemVersion;
+
+/// Read tracking of a node, empty without the `access` feature.
+#[derive(Debug, Default)]
+pub(crate) struct NodeAccess {
+    #[cfg(feature = "access")]
+    last_read: AtomicU64,
+}
+
+impl NodeAccess {
+    #[inline]
+    pub(crate) fn record(&self, version: SystemVersion) {
+        #[cfg(feature = "access")]
+        self.last_read
+            .fetch_max(version.version.get(), Ordering::Relaxed);
+        #[cfg(not(feature = "access"))]
+        let _ = version;
+    }
+
+    #[cfg(feature = "access")]
+    pub(crate) fn last_read_version(&self) -> Option<SystemVersion> {
+        std::num::NonZeroU64::new(self.last_read.load(Ordering::Relaxed))
+            .map(|version| SystemVersion { version })
+    }
+}
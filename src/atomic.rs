@@ -0,0 +1,76 @@
+use std::sync::RwLock;
+
+use crate::error::LazyCatchError;
+use crate::{System, SystemModify};
+
+/// A [`System`] shared between threads that read and write it.
+///
+/// Reads run under a shared lock and writes under an exclusive one, so a writer waits until
+/// every running read finished, and a read never sees a modify that started after it.
+/// Reads run in parallel, a stale [`SyncVal`](crate::val::SyncVal) read by two of them at once
+/// is updated by one while the other waits for its value.
+///
+/// ```
+/// # use std::time::Duration;
+/// # use lazy_catch::atomic::AtomicSystem;
+/// # use lazy_catch::{System, Update};
+/// let system = System::new();
+/// let x = system.var(1);
+/// let double = system.sync_val(|mut u: Update<i32>| {
+///     let x = *u.get(&x);
+///     std::thread::sleep(Duration::from_micros(100));
+///     u.update(|| x * 2);
+/// });
+/// let system = AtomicSystem::new(system);
+/// std::thread::scope(|scope| {
+///     scope.spawn(|| {
+///         for n in 2..100 {
+///             system.modify(|modify| *x.modify(modify) = n);
+///         }
+///     });
+///     for _ in 0..4 {
+///         scope.spawn(|| {
+///             for _ in 0..100 {
+///                 let (x, double) =
+///                     system.read_consistent(|system| (*system.get(&x), *system.get(&double)));
+///                 assert_eq!(double, x * 2);
+///             }
+///         });
+///     }
+/// });
+/// ```
+#[derive(Debug, Default)]
+pub struct AtomicSystem {
+    system: RwLock<System>,
+}
+
+impl AtomicSystem {
+    pub fn new(system: System) -> Self {
+        Self {
+            system: RwLock::new(system),
+        }
+    }
+
+    /// Run `f` on the system at one version, writers wait until it returns.
+    pub fn read_consistent<R>(&self, f: impl FnOnce(&System) -> R) -> R {
+        match self.system.read() {
+            Ok(system) => f(&system),
+            Err(_) => LazyCatchError::Poisoned.raise(),
+        }
+    }
+
+    /// Run `f` in a new modify, after every running read finished.
+    pub fn modify<R>(&self, f: impl FnOnce(&mut SystemModify) -> R) -> R {
+        match self.system.write() {
+            Ok(mut system) => f(&mut system.modify()),
+            Err(_) => LazyCatchError::Poisoned.raise(),
+        }
+    }
+
+    pub fn into_inner(self) -> System {
+        match self.system.into_inner() {
+            Ok(system) => system,
+            Err(_) => LazyCatchError::Poisoned.raise(),
+        }
+    }
+}
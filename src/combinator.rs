@@ -0,0 +1,590 @@
+use std::borrow::Cow;
+use std::collections::VecDeque;
+
+use crate::val::Val;
+use crate::{System, SystemNode, SystemVersion, Update};
+
+impl System {
+    /// A copy of `node` that only follows its changes at [`System::flush_coalesced`].
+    ///
+    /// `node` is read once when the copy is created, so until the first flush the copy returns
+    /// that value. After a flush the first read takes the value of `node` again and holds it
+    /// until the next flush.
+    ///
+    /// ```
+    /// # use lazy_catch::System;
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let c = system.coalesce(&x);
+    /// *x.modify(&mut system.modify()) = 2;
+    /// *x.modify(&mut system.modify()) = 3;
+    /// assert_eq!(*system.get(&c), 1);
+    /// system.flush_coalesced();
+    /// assert_eq!(*system.get(&c), 3);
+    /// *x.modify(&mut system.modify()) = 4;
+    /// assert_eq!(*system.get(&c), 3);
+    /// ```
+    ///
+    /// The value is taken at the first read after a flush, not at the flush itself.
+    ///
+    /// ```
+    /// # use lazy_catch::System;
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let c = system.coalesce(&x);
+    /// system.flush_coalesced();
+    /// *x.modify(&mut system.modify()) = 2;
+    /// assert_eq!(*system.get(&c), 2);
+    /// *x.modify(&mut system.modify()) = 3;
+    /// assert_eq!(*system.get(&c), 2);
+    /// ```
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn coalesce<'n, N: SystemNode + ?Sized>(
+        &self,
+        node: &'n N,
+    ) -> Val<N::Value, impl FnMut(Update<N::Value>) + 'n>
+    where
+        N::Value: Clone,
+    {
+        let (mut seen, value) = node.get_value(self);
+        let mut seed = Some(value.clone());
+        let mut flushed = self.coalesce_version;
+        self.val(move |mut u: Update<N::Value>| {
+            // Depend on the flush, the changes of `node` only count once flushed.
+            let flush = u.system().coalesce_version;
+            u.track_node(node.node_id(), flush);
+            let seed = seed.take();
+            if flushed == flush {
+                return match seed {
+                    Some(seed) => u.update(|| seed),
+                    None => u.keep(),
+                };
+            }
+            flushed = flush;
+            let (version, value) = node.get_value(u.system());
+            if seen >= version && seed.is_none() {
+                return u.keep();
+            }
+            seen = version;
+            u.update(|| value.clone());
+        })
+    }
+
+    /// Expose the changes held back by [`System::coalesce`] nodes.
+    pub fn flush_coalesced(&mut self) {
+        self.modify();
+        self.coalesce_version = self.version;
+    }
+
+    /// Count the changes of `node` seen by reads of this node, starting from 0.
+    ///
+    /// Several changes between two reads count once.
+    ///
+    /// ```
+    /// # use lazy_catch::System;
+    /// let mut system = System::new();
+    /// let x = system.var(0);
+    /// let y = system.var(0);
+    /// let count = system.change_count(&x);
+    /// assert_eq!(*system.get(&count), 0);
+    /// *x.modify(&mut system.modify()) = 1;
+    /// *y.modify(&mut system.modify()) = 1;
+    /// assert_eq!(*system.get(&count), 1);
+    /// *x.modify(&mut system.modify()) = 2;
+    /// assert_eq!(*system.get(&count), 2);
+    /// ```
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn change_count<'n, N: SystemNode + ?Sized>(
+        &self,
+        node: &'n N,
+    ) -> Val<u64, impl FnMut(Update<u64>) + 'n> {
+        self.val(move |mut u: Update<u64>| {
+            u.get_versioned(node);
+            u.update_with_old(|count| count.map_or(0, |count| count + 1));
+        })
+    }
+
+    /// Map `node` by a function that may pass its input through unchanged.
+    ///
+    /// A node can not borrow from its dependencies, so a borrowed result is cloned into the
+    /// stored value. The function only runs when `node` changed, so a passthrough costs one
+    /// clone per upstream change and nothing on other modifies.
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// # use lazy_catch::System;
+    /// let mut system = System::new();
+    /// let name = system.var(String::from("  lazy "));
+    /// let trimmed = system.map_cow(&name, |name: &String| {
+    ///     if name.trim() == name {
+    ///         Cow::Borrowed(name.as_str())
+    ///     } else {
+    ///         Cow::Owned(name.trim().to_string())
+    ///     }
+    /// });
+    /// assert_eq!(system.get(&trimmed), "lazy");
+    /// *name.modify(&mut system.modify()) = String::from("catch");
+    /// assert_eq!(system.get(&trimmed), "catch");
+    /// ```
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn map_cow<'n, N: SystemNode + ?Sized, B: ToOwned + ?Sized, F>(
+        &self,
+        node: &'n N,
+        mut f: F,
+    ) -> Val<B::Owned, impl FnMut(Update<B::Owned>) + 'n>
+    where
+        F: FnMut(&N::Value) -> Cow<'_, B> + 'n,
+    {
+        self.val(move |mut u: Update<B::Owned>| {
+            let value = u.get_versioned(node).1;
+            u.update(|| f(value).into_owned());
+        })
+    }
+
+    /// Fold the values of a fixed set of nodes in one pass, storing a new value only when one
+    /// of them changed.
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use lazy_catch::System;
+    /// let mut system = System::new();
+    /// let xs: Vec<_> = (1..=3).map(|x| Arc::new(system.var(x))).collect();
+    /// let sum = system.reduce(&xs, || 0, |sum, x| sum + x);
+    /// assert_eq!(*system.get(&sum), 6);
+    /// *xs[1].modify(&mut system.modify()) = 10;
+    /// assert_eq!(*system.get(&sum), 14);
+    /// ```
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn reduce<'n, N: SystemNode, A, I: Fn() -> A + 'n, F: FnMut(A, &N::Value) -> A + 'n>(
+        &self,
+        nodes: &'n [N],
+        init: I,
+        mut f: F,
+    ) -> Val<A, impl FnMut(Update<A>) + 'n> {
+        self.val(move |mut u: Update<A>| {
+            let mut acc = init();
+            for node in nodes {
+                acc = f(acc, u.get(node));
+            }
+            u.update(|| acc);
+        })
+    }
+
+    /// Map `node`, remembering the results for its last `capacity` versions.
+    ///
+    /// When `node` returns to a version still in the cache, the result is reused
+    /// instead of calling `f`. The least recently used version is evicted first.
+    /// A version always identifies one value of `node`, so a reused result is never stale.
+    ///
+    /// The built-in nodes never go back to an older version, this pays off for nodes
+    /// that switch between upstreams and forward their versions.
+    ///
+    /// ```
+    /// # use std::cell::Cell;
+    /// # use lazy_catch::var::Var;
+    /// # use lazy_catch::{NodeId, System, SystemId, SystemNode, SystemVersion};
+    /// struct Pick<'a> {
+    ///     a: &'a Var<i32>,
+    ///     b: &'a Var<i32>,
+    ///     use_b: Cell<bool>,
+    /// }
+    ///
+    /// impl SystemNode for Pick<'_> {
+    ///     type Value = i32;
+    ///
+    ///     fn node_id(&self) -> NodeId {
+    ///         self.a.node_id()
+    ///     }
+    ///
+    ///     fn system_id(&self) -> SystemId {
+    ///         self.a.system_id()
+    ///     }
+    ///
+    ///     fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s i32) {
+    ///         if self.use_b.get() {
+    ///             self.b.get_value(system)
+    ///         } else {
+    ///             self.a.get_value(system)
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut system = System::new();
+    /// let a = system.var(2);
+    /// let b = system.var(0);
+    /// system.set(&b, 3);
+    /// let pick = Pick { a: &a, b: &b, use_b: Cell::new(false) };
+    /// let runs = Cell::new(0);
+    /// let squared = system.val_cache_versions(&pick, 2, |x: &i32| {
+    ///     runs.set(runs.get() + 1);
+    ///     x * x
+    /// });
+    /// assert_eq!(*system.get(&squared), 4);
+    /// pick.use_b.set(true);
+    /// system.modify();
+    /// assert_eq!(*system.get(&squared), 9);
+    /// pick.use_b.set(false);
+    /// system.modify();
+    /// assert_eq!(*system.get(&squared), 4);
+    /// assert_eq!(runs.get(), 2);
+    /// ```
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn val_cache_versions<
+        'n,
+        N: SystemNode + ?Sized,
+        T: Clone + 'n,
+        F: FnMut(&N::Value) -> T + 'n,
+    >(
+        &self,
+        node: &'n N,
+        capacity: usize,
+        mut f: F,
+    ) -> Val<T, impl FnMut(Update<T>) + 'n> {
+        assert!(capacity > 0, "cache capacity must not be zero");
+        let mut seen = None;
+        let mut cache: VecDeque<(SystemVersion, T)> = VecDeque::with_capacity(capacity);
+        self.val(move |mut u: Update<T>| {
+            let (version, value) = node.get_value(u.system());
+            if seen == Some(version) {
+                return u.keep();
+            }
+            seen = Some(version);
+            u.depend_on_version();
+            let result = match cache.iter().position(|&(v, _)| v == version) {
+                Some(index) => cache.remove(index).unwrap().1,
+                None => f(value),
+            };
+            if cache.len() == capacity {
+                cache.pop_front();
+            }
+            cache.push_back((version, result.clone()));
+            u.update(|| result);
+        })
+    }
+
+    /// Combine two `Result` nodes into `Ok` of both values, or the first `Err` found.
+    ///
+    /// `b` is only read, and only becomes a dependency, when `a` is `Ok`.
+    ///
+    /// ```
+    /// # use lazy_catch::System;
+    /// let mut system = System::new();
+    /// let a = system.var(Ok::<i32, String>(1));
+    /// let b = system.var(Ok::<i32, String>(2));
+    /// let both = system.try_zip2(&a, &b);
+    /// assert_eq!(*system.get(&both), Ok((1, 2)));
+    /// system.set(&b, Err(String::from("b failed")));
+    /// assert_eq!(*system.get(&both), Err(String::from("b failed")));
+    /// system.set(&a, Err(String::from("a failed")));
+    /// assert_eq!(*system.get(&both), Err(String::from("a failed")));
+    /// ```
+    #[must_use = "nodes must be stored to be read later"]
+    #[allow(clippy::type_complexity)]
+    pub fn try_zip2<'n, A: Clone + 'n, B: Clone + 'n, E: Clone + 'n, NA, NB>(
+        &self,
+        a: &'n NA,
+        b: &'n NB,
+    ) -> Val<Result<(A, B), E>, impl FnMut(Update<Result<(A, B), E>>) + 'n>
+    where
+        NA: SystemNode<Value = Result<A, E>> + ?Sized,
+        NB: SystemNode<Value = Result<B, E>> + ?Sized,
+    {
+        self.val(move |mut u: Update<Result<(A, B), E>>| {
+            let values = (|| Ok((read_ok(&mut u, a)?, read_ok(&mut u, b)?)))();
+            u.update(|| values.map(|(a, b)| (a.clone(), b.clone())));
+        })
+    }
+
+    /// Like [`System::try_zip2`], for three nodes read in order.
+    #[must_use = "nodes must be stored to be read later"]
+    #[allow(clippy::type_complexity)]
+    pub fn try_zip3<'n, A: Clone + 'n, B: Clone + 'n, C: Clone + 'n, E: Clone + 'n, NA, NB, NC>(
+        &self,
+        a: &'n NA,
+        b: &'n NB,
+        c: &'n NC,
+    ) -> Val<Result<(A, B, C), E>, impl FnMut(Update<Result<(A, B, C), E>>) + 'n>
+    where
+        NA: SystemNode<Value = Result<A, E>> + ?Sized,
+        NB: SystemNode<Value = Result<B, E>> + ?Sized,
+        NC: SystemNode<Value = Result<C, E>> + ?Sized,
+    {
+        self.val(move |mut u: Update<Result<(A, B, C), E>>| {
+            let values = (|| {
+                Ok((
+                    read_ok(&mut u, a)?,
+                    read_ok(&mut u, b)?,
+                    read_ok(&mut u, c)?,
+                ))
+            })();
+            u.update(|| values.map(|(a, b, c)| (a.clone(), b.clone(), c.clone())));
+        })
+    }
+
+    /// The largest value `node` has taken at the reads of this node.
+    ///
+    /// The value and its version only change when a new maximum is seen.
+    ///
+    /// ```
+    /// # use lazy_catch::System;
+    /// let mut system = System::new();
+    /// let x = system.var(3);
+    /// let max = system.running_max(&x);
+    /// assert_eq!(*system.get(&max), 3);
+    /// system.set(&x, 1);
+    /// let version = system.get_versioned(&max).0;
+    /// assert_eq!(*system.get(&max), 3);
+    /// system.set(&x, 5);
+    /// assert_eq!(*system.get(&max), 5);
+    /// assert!(system.get_versioned(&max).0 > version);
+    /// ```
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn running_max<'n, N: SystemNode + ?Sized>(
+        &self,
+        node: &'n N,
+    ) -> Val<N::Value, impl FnMut(Update<N::Value>) + 'n>
+    where
+        N::Value: Clone + Ord,
+    {
+        self.running_extreme(node, |new, old| new > old)
+    }
+
+    /// The smallest value `node` has taken at the reads of this node.
+    ///
+    /// The value and its version only change when a new minimum is seen.
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn running_min<'n, N: SystemNode + ?Sized>(
+        &self,
+        node: &'n N,
+    ) -> Val<N::Value, impl FnMut(Update<N::Value>) + 'n>
+    where
+        N::Value: Clone + Ord,
+    {
+        self.running_extreme(node, |new, old| new < old)
+    }
+
+    fn running_extreme<'n, N: SystemNode + ?Sized>(
+        &self,
+        node: &'n N,
+        better: fn(&N::Value, &N::Value) -> bool,
+    ) -> Val<N::Value, impl FnMut(Update<N::Value>) + 'n>
+    where
+        N::Value: Clone,
+    {
+        let mut extreme: Option<N::Value> = None;
+        self.val(move |mut u: Update<N::Value>| {
+            let value = u.get_versioned(node).1;
+            if extreme.as_ref().is_some_and(|old| !better(value, old)) {
+                return u.keep();
+            }
+            extreme = Some(value.clone());
+            u.update(|| value.clone());
+        })
+    }
+
+    /// The previous and current value of `node`, both as seen by reads of this node.
+    ///
+    /// The first value has no previous one.
+    ///
+    /// ```
+    /// # use lazy_catch::System;
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let pair = system.pairwise(&x);
+    /// assert_eq!(*system.get(&pair), (None, 1));
+    /// system.set(&x, 4);
+    /// assert_eq!(*system.get(&pair), (Some(1), 4));
+    /// system.set(&x, 6);
+    /// assert_eq!(*system.get(&pair), (Some(4), 6));
+    /// ```
+    #[must_use = "nodes must be stored to be read later"]
+    #[allow(clippy::type_complexity)]
+    pub fn pairwise<'n, N: SystemNode + ?Sized>(
+        &self,
+        node: &'n N,
+    ) -> Val<(Option<N::Value>, N::Value), impl FnMut(Update<(Option<N::Value>, N::Value)>) + 'n>
+    where
+        N::Value: Clone,
+    {
+        self.val(move |mut u: Update<(Option<N::Value>, N::Value)>| {
+            let value = u.get_versioned(node).1;
+            u.update_with_old(|old| (old.map(|(_, current)| current), value.clone()));
+        })
+    }
+
+    /// The change of `a` or `b`, or of both, seen by each read of this node.
+    ///
+    /// The first read gives both values.
+    ///
+    /// ```
+    /// # use lazy_catch::combinator::Merged;
+    /// # use lazy_catch::System;
+    /// let mut system = System::new();
+    /// let a = system.var(1);
+    /// let b = system.var("x");
+    /// let merged = system.merge(&a, &b);
+    /// assert_eq!(*system.get(&merged), Merged::Both(1, "x"));
+    /// system.set(&a, 2);
+    /// assert_eq!(*system.get(&merged), Merged::Left(2));
+    /// system.set(&b, "y");
+    /// assert_eq!(*system.get(&merged), Merged::Right("y"));
+    /// ```
+    #[must_use = "nodes must be stored to be read later"]
+    #[allow(clippy::type_complexity)]
+    pub fn merge<'n, A: SystemNode + ?Sized, B: SystemNode + ?Sized>(
+        &self,
+        a: &'n A,
+        b: &'n B,
+    ) -> Val<Merged<A::Value, B::Value>, impl FnMut(Update<Merged<A::Value, B::Value>>) + 'n>
+    where
+        A::Value: Clone,
+        B::Value: Clone,
+    {
+        let mut seen_a = None;
+        let mut seen_b = None;
+        self.val(move |mut u: Update<Merged<A::Value, B::Value>>| {
+            let (version_a, value_a) = u.get_versioned(a);
+            let (version_b, value_b) = u.get_versioned(b);
+            let changed_a = seen_a != Some(version_a);
+            let changed_b = seen_b != Some(version_b);
+            if !changed_a && !changed_b {
+                return u.keep();
+            }
+            seen_a = Some(version_a);
+            seen_b = Some(version_b);
+            u.update(|| match (changed_a, changed_b) {
+                (true, false) => Merged::Left(value_a.clone()),
+                (false, true) => Merged::Right(value_b.clone()),
+                _ => Merged::Both(value_a.clone(), value_b.clone()),
+            });
+        })
+    }
+
+    /// The value of `node` with the number of modifies its version advanced by since the value
+    /// this node stored before, `0` at the first read.
+    ///
+    /// Changes between two reads are collapsed into one update. Only versions are seen, so
+    /// the count includes modifies that did not change `node`: it is an upper bound of the
+    /// collapsed changes, exact when every modify changed it.
+    ///
+    /// ```
+    /// # use lazy_catch::System;
+    /// let mut system = System::new();
+    /// let x = system.var(0);
+    /// let collapsed = system.collapsing(&x);
+    /// assert_eq!(*system.get(&collapsed), (0, 0));
+    /// system.set(&x, 1);
+    /// assert_eq!(*system.get(&collapsed), (1, 1));
+    /// for n in 2..5 {
+    ///     system.set(&x, n);
+    /// }
+    /// assert_eq!(*system.get(&collapsed), (4, 3));
+    /// system.modify();
+    /// assert_eq!(*system.get(&collapsed), (4, 3));
+    /// ```
+    #[must_use = "nodes must be stored to be read later"]
+    #[allow(clippy::type_complexity)]
+    pub fn collapsing<'n, N: SystemNode + ?Sized>(
+        &self,
+        node: &'n N,
+    ) -> Val<(N::Value, usize), impl FnMut(Update<(N::Value, usize)>) + 'n>
+    where
+        N::Value: Clone,
+    {
+        self.val(move |mut u: Update<(N::Value, usize)>| {
+            let (version, value) = u.get_versioned(node);
+            u.update_with_old_versioned(|old| {
+                let collapsed = old.map_or(0, |(old, _)| version.get().get() - old.get().get());
+                (value.clone(), collapsed as usize)
+            });
+        })
+    }
+
+    /// `true` at the first read after `cond` went through the edge `kind`, `false` otherwise.
+    ///
+    /// The value goes back to `false` at the first read after any later modify, so each edge
+    /// is seen once by readers. The previous value of `cond` is the one seen by the last read
+    /// of this node: an edge undone before the next read is not seen.
+    ///
+    /// ```
+    /// # use lazy_catch::combinator::EdgeKind;
+    /// # use lazy_catch::System;
+    /// let mut system = System::new();
+    /// let pressed = system.var(false);
+    /// let other = system.var(0);
+    /// let click = system.edge(&pressed, EdgeKind::Rising);
+    /// let release = system.edge(&pressed, EdgeKind::Falling);
+    /// assert!(!*system.get(&click));
+    /// system.set(&pressed, true);
+    /// assert!(*system.get(&click));
+    /// assert!(!*system.get(&release));
+    /// let clicked = system.get_versioned(&click).0;
+    /// assert_eq!(clicked, system.version());
+    /// system.set(&other, 1);
+    /// assert!(!*system.get(&click));
+    /// system.set(&pressed, false);
+    /// assert!(!*system.get(&click));
+    /// assert!(*system.get(&release));
+    /// ```
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn edge<'n, C: SystemNode<Value = bool> + ?Sized>(
+        &self,
+        cond: &'n C,
+        kind: EdgeKind,
+    ) -> Val<bool, impl FnMut(Update<bool>) + 'n> {
+        let mut previous = None;
+        let mut emitted = false;
+        self.val(move |mut u: Update<bool>| {
+            let value = *u.get_versioned(cond).1;
+            let fired = previous
+                .replace(value)
+                .is_some_and(|was| kind.is_edge(was, value));
+            if u.current_version.is_some() && fired == emitted {
+                return u.keep();
+            }
+            emitted = fired;
+            u.depend_on_version();
+            u.update(|| fired);
+        })
+    }
+}
+
+/// Read a `Result` node and depend on it, with a clone of its error.
+fn read_ok<'r, 's: 'r, T: 'r, E: Clone + 'r, U, N>(
+    u: &mut Update<'s, U>,
+    node: &'r N,
+) -> Result<&'r T, E>
+where
+    N: SystemNode<Value = Result<T, E>> + ?Sized,
+{
+    u.get_versioned(node).1.as_ref().map_err(E::clone)
+}
+
+/// The transition watched by [`System::edge`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum EdgeKind {
+    /// From `false` to `true`.
+    Rising,
+    /// From `true` to `false`.
+    Falling,
+}
+
+impl EdgeKind {
+    fn is_edge(self, was: bool, value: bool) -> bool {
+        match self {
+            EdgeKind::Rising => !was && value,
+            EdgeKind::Falling => was && !value,
+        }
+    }
+}
+
+/// The value of [`System::merge`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Merged<A, B> {
+    /// Only the first node changed.
+    Left(A),
+    /// Only the second node changed.
+    Right(B),
+    /// Both nodes changed since the last read, or it is the first read.
+    Both(A, B),
+}
@@ -0,0 +1,114 @@
+use std::cell::RefCell;
+
+use crate::error::LazyCatchError;
+use crate::val::Val;
+use crate::{NodeId, System, SystemId, SystemNode, SystemVersion, Update};
+
+impl System {
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn val_with_drop<T, F: FnMut(Update<T>), D: FnMut(&T)>(
+        &self,
+        f: F,
+        on_drop: D,
+    ) -> DropVal<T, F, D> {
+        DropVal::new(self, f, on_drop)
+    }
+}
+
+/// A val that runs `on_drop` on each value it lets go of.
+///
+/// When [`Update::update`] stores a new value, `on_drop` runs on the old one first.
+/// `update_with_old` hands the old value to its closure instead, so `on_drop` does not run.
+/// When the node drops, `on_drop` runs on its last value, if any.
+///
+/// ```
+/// # use std::cell::RefCell;
+/// # use lazy_catch::{System, Update};
+/// let released = RefCell::new(Vec::new());
+/// let mut system = System::new();
+/// let x = system.var(1);
+/// let handle = system.val_with_drop(
+///     |mut u: Update<i32>| {
+///         let x = *u.get(&x);
+///         u.update(|| x);
+///     },
+///     |handle: &i32| released.borrow_mut().push(*handle),
+/// );
+/// system.get(&handle);
+/// system.set(&x, 2);
+/// system.get(&handle);
+/// assert_eq!(*released.borrow(), [1]);
+/// drop(handle);
+/// assert_eq!(*released.borrow(), [1, 2]);
+/// ```
+pub struct DropVal<T, F, D: FnMut(&T)> {
+    val: Val<T, F>,
+    on_drop: RefCell<D>,
+}
+
+impl<T, F: FnMut(Update<T>), D: FnMut(&T)> DropVal<T, F, D> {
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn new(system: &System, f: F, on_drop: D) -> Self {
+        Self {
+            val: Val::new(system, f),
+            on_drop: RefCell::new(on_drop),
+        }
+    }
+
+    /// The system version when this node was created.
+    pub fn created_version(&self) -> SystemVersion {
+        self.val.created_version()
+    }
+
+    /// The dependency with the newest version when the value was last stored,
+    /// `None` if it read no dependency.
+    #[cfg(feature = "trace")]
+    pub fn last_trigger(&self) -> Option<NodeId> {
+        self.val.last_trigger()
+    }
+}
+
+impl<T, F: FnMut(Update<T>), D: FnMut(&T)> SystemNode for DropVal<T, F, D> {
+    type Value = T;
+
+    fn node_id(&self) -> NodeId {
+        self.val.node_id()
+    }
+
+    fn system_id(&self) -> SystemId {
+        self.val.system_id()
+    }
+
+    fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
+        match self.try_get_value(system) {
+            Some(value) => value,
+            None => LazyCatchError::Aborted.raise(),
+        }
+    }
+
+    fn try_get_value<'s>(&'s self, system: &'s System) -> Option<(SystemVersion, &'s Self::Value)> {
+        let on_replace = |old: &T| (self.on_drop.borrow_mut())(old);
+        self.val.try_get_value_with(system, Some(&on_replace))
+    }
+
+    fn is_computed(&self) -> bool {
+        self.val.is_computed()
+    }
+
+    fn is_current(&self, system: &System) -> bool {
+        self.val.is_current(system)
+    }
+
+    #[cfg(feature = "trace")]
+    fn last_deps(&self) -> Vec<(NodeId, SystemVersion)> {
+        self.val.last_deps()
+    }
+}
+
+impl<T, F, D: FnMut(&T)> Drop for DropVal<T, F, D> {
+    fn drop(&mut self) {
+        if let Some(value) = self.val.stored_value() {
+            self.on_drop.get_mut()(value);
+        }
+    }
+}
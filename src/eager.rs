@@ -0,0 +1,141 @@
+use std::fmt::{self, Debug, Formatter};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread::{Scope, ScopedJoinHandle};
+
+use crate::val::{SyncVal, ValLock};
+use crate::{System, SystemNode, SystemVersion, Update};
+
+impl System {
+    /// A node that is brought up to date when each [`SystemModify`](crate::SystemModify) ends,
+    /// instead of at its next read.
+    ///
+    /// Eager nodes are pulled in the order they were created. An eager node that reads another
+    /// eager node created after it pulls that one early, which then only checks again.
+    /// The system keeps the node only while the returned `Arc` is alive.
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use std::sync::atomic::{AtomicUsize, Ordering};
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let x = Arc::new(system.var(1));
+    /// let runs = Arc::new(AtomicUsize::new(0));
+    /// let a = system.eager_val({
+    ///     let x = x.clone();
+    ///     let runs = runs.clone();
+    ///     move |mut u: Update<i32>| {
+    ///         runs.fetch_add(1, Ordering::Relaxed);
+    ///         let x = *u.get(&*x);
+    ///         u.update(|| x * 2);
+    ///     }
+    /// });
+    /// assert_eq!(runs.load(Ordering::Relaxed), 0);
+    /// system.set(&x, 2);
+    /// assert_eq!(runs.load(Ordering::Relaxed), 1);
+    /// assert_eq!(*system.get(&*a), 4);
+    /// assert_eq!(runs.load(Ordering::Relaxed), 1);
+    /// ```
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn eager_val<T, F>(&self, f: F) -> Arc<SyncVal<T, F>>
+    where
+        T: Send + Sync + 'static,
+        F: FnMut(Update<T>) + Send + 'static,
+    {
+        let node = Arc::new(SyncVal::new(self, f));
+        let weak: Weak<dyn EagerNode> = Arc::downgrade(&node) as _;
+        self.eager.push(weak);
+        node
+    }
+
+    /// Bring `node` up to date on a thread of `scope`, so that a later read at the same version
+    /// finds its value cached.
+    ///
+    /// The handle returns the version the value was computed for. The thread borrows the
+    /// system, so no modify can start before the scope ends and the value can not be stale
+    /// when it is stored; a modify after that only makes the next read check the dependencies
+    /// again, as for any node. A read of `node` on another thread that finds the update running
+    /// waits for it.
+    ///
+    /// ```
+    /// # use std::sync::atomic::{AtomicUsize, Ordering};
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let x = system.var(5);
+    /// let runs = AtomicUsize::new(0);
+    /// let factorial = system.sync_val(|mut u: Update<u64>| {
+    ///     runs.fetch_add(1, Ordering::Relaxed);
+    ///     let x = *u.get(&x);
+    ///     u.update(|| (1..=x).product());
+    /// });
+    /// std::thread::scope(|scope| {
+    ///     let prefetch = system.prefetch(&factorial, scope);
+    ///     assert_eq!(prefetch.join().unwrap(), system.version());
+    /// });
+    /// assert_eq!(runs.load(Ordering::Relaxed), 1);
+    /// assert_eq!(*system.get(&factorial), 120);
+    /// assert_eq!(runs.load(Ordering::Relaxed), 1);
+    /// system.set(&x, 3);
+    /// assert_eq!(*system.get(&factorial), 6);
+    /// assert_eq!(runs.load(Ordering::Relaxed), 2);
+    /// ```
+    pub fn prefetch<'scope, 'env, N: SystemNode + Sync + ?Sized>(
+        &'env self,
+        node: &'env N,
+        scope: &'scope Scope<'scope, 'env>,
+    ) -> ScopedJoinHandle<'scope, SystemVersion> {
+        node.system_id().check_system(self);
+        scope.spawn(move || {
+            node.get_value(self);
+            self.version()
+        })
+    }
+
+    pub(crate) fn pull_eager(&self) {
+        for node in self.eager.alive() {
+            node.pull(self);
+        }
+    }
+}
+
+pub(crate) trait EagerNode: Send + Sync {
+    fn pull(&self, system: &System);
+}
+
+impl<T, F, L> EagerNode for SyncVal<T, F, L>
+where
+    T: Send + Sync,
+    F: FnMut(Update<T>) + Send,
+    L: ValLock + Send + Sync,
+{
+    fn pull(&self, system: &System) {
+        self.warm(system);
+    }
+}
+
+/// The eager nodes of a system, in creation order.
+#[derive(Default)]
+pub(crate) struct EagerNodes {
+    nodes: Mutex<Vec<Weak<dyn EagerNode>>>,
+}
+
+impl EagerNodes {
+    fn push(&self, node: Weak<dyn EagerNode>) {
+        let mut nodes = self.nodes.lock().unwrap();
+        nodes.retain(|node| node.strong_count() > 0);
+        nodes.push(node);
+    }
+
+    /// The nodes still alive, collected so that pulling them may create new eager nodes.
+    fn alive(&self) -> Vec<Arc<dyn EagerNode>> {
+        let mut nodes = self.nodes.lock().unwrap();
+        nodes.retain(|node| node.strong_count() > 0);
+        nodes.iter().filter_map(Weak::upgrade).collect()
+    }
+}
+
+impl Debug for EagerNodes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let len = self.nodes.lock().unwrap().len();
+        f.debug_struct("EagerNodes").field("len", &len).finish()
+    }
+}
@@ -0,0 +1,63 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// Failures of the crate, also used as the payload of its panics.
+///
+/// ```
+/// # use std::panic::{catch_unwind, AssertUnwindSafe};
+/// # use lazy_catch::System;
+/// # use lazy_catch::error::LazyCatchError;
+/// let system = System::new();
+/// let x = System::new().var(0);
+/// let payload = catch_unwind(AssertUnwindSafe(|| *system.get(&x))).unwrap_err();
+/// assert_eq!(
+///     payload.downcast_ref::<LazyCatchError>(),
+///     Some(&LazyCatchError::WrongSystem),
+/// );
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum LazyCatchError {
+    /// A node was read while its own update was running.
+    Recursion,
+    /// The node belongs to a different system.
+    WrongSystem,
+    /// The system version can not be incremented any more.
+    VersionExhausted,
+    /// A lock was poisoned by a panic in an update.
+    Poisoned,
+    /// A node had to run its update while an observer callback was running.
+    ObserverUpdate,
+    /// A modify was started at a version that is not newer than the current one.
+    VersionNotIncreasing,
+    /// A node marked with `System::mark_constant` of the `trace` feature was written.
+    ConstantWrite,
+    /// A node was read whose update aborted without a previous value.
+    Aborted,
+    /// A [`ParamNode`](crate::param::ParamNode) was read as a node before any parameter was given.
+    MissingParam,
+}
+
+impl LazyCatchError {
+    /// Panic with this error as the payload.
+    pub fn raise(self) -> ! {
+        std::panic::panic_any(self)
+    }
+}
+
+impl Display for LazyCatchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LazyCatchError::Recursion => f.write_str("Val update recursion"),
+            LazyCatchError::WrongSystem => f.write_str("node belongs to a different system"),
+            LazyCatchError::VersionExhausted => f.write_str("system version exhausted"),
+            LazyCatchError::Poisoned => f.write_str("Val update poison"),
+            LazyCatchError::ObserverUpdate => f.write_str("Val update in observer callback"),
+            LazyCatchError::VersionNotIncreasing => f.write_str("system version not increasing"),
+            LazyCatchError::ConstantWrite => f.write_str("write to a constant node"),
+            LazyCatchError::Aborted => f.write_str("Val update aborted"),
+            LazyCatchError::MissingParam => f.write_str("ParamNode read before any param"),
+        }
+    }
+}
+
+impl Error for LazyCatchError {}
@@ -0,0 +1,173 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::error::LazyCatchError;
+use crate::val::{BoxedVal, Val};
+use crate::{NodeId, System, SystemId, SystemNode, SystemVersion, Tag, Update};
+
+impl System {
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn fast_val<'n, T: 'n, F: FnMut(&mut FastDeps<'n>, Update<T>) + 'n>(
+        &self,
+        f: F,
+    ) -> FastVal<'n, T> {
+        FastVal::new(self, f)
+    }
+}
+
+/// A node read only for its version, see [`FastVal`].
+pub trait Dependency {
+    /// Bring the node up to date and return the version of its value.
+    fn version(&self, system: &System) -> SystemVersion;
+}
+
+impl<N: SystemNode + ?Sized> Dependency for N {
+    fn version(&self, system: &System) -> SystemVersion {
+        self.get_value(system).0
+    }
+}
+
+type SeenDeps<'n> = Rc<RefCell<Vec<(&'n dyn Dependency, SystemVersion)>>>;
+
+/// The nodes read by a run of the closure of a [`FastVal`], with the versions it saw.
+pub struct FastDeps<'n> {
+    deps: Vec<(&'n dyn Dependency, SystemVersion)>,
+}
+
+impl<'n> FastDeps<'n> {
+    /// Read `node` like [`Update::get`], and check its version at the next read of the node.
+    pub fn get<'r, 's: 'r, T, N: SystemNode>(
+        &mut self,
+        u: &mut Update<'s, T>,
+        node: &'n N,
+    ) -> &'r N::Value
+    where
+        'n: 'r,
+    {
+        let (version, value) = u.get_versioned(node);
+        self.deps.push((node, version));
+        value
+    }
+}
+
+/// A val that remembers the nodes its closure read, skipping the closure when none of them
+/// advanced.
+///
+/// A [`Val`] runs its closure at the first read after every modify, to learn from the versions
+/// it reads that nothing changed. This node reads its dependencies through [`FastDeps::get`],
+/// which keeps them with the versions seen by the last run. At the next read it first reads the
+/// versions of those nodes, and if none advanced it only marks the val as checked, so a modify
+/// costs one version read per dependency instead of a run of the closure. That costs about as
+/// much as a closure that only reads its dependencies, so it pays off for closures doing more
+/// work, as measured by `benches/fast_path.rs`.
+///
+/// A node read with [`Update::get`] instead is not checked, a change of it alone is missed.
+/// A run that read no dependency through [`FastDeps`] runs again at every check, like a val
+/// without dependencies.
+///
+/// ```
+/// # use std::cell::Cell;
+/// # use lazy_catch::{System, Update};
+/// let mut system = System::new();
+/// let flag = system.var(true);
+/// let x = system.var(1);
+/// let y = system.var(10);
+/// let runs = Cell::new(0);
+/// let a = system.fast_val(|deps, mut u: Update<i32>| {
+///     runs.set(runs.get() + 1);
+///     let v = if *deps.get(&mut u, &flag) {
+///         *deps.get(&mut u, &x)
+///     } else {
+///         *deps.get(&mut u, &y)
+///     };
+///     u.update(|| v * 2);
+/// });
+/// assert_eq!(*system.get(&a), 2);
+/// system.set(&y, 20);
+/// assert_eq!(*system.get(&a), 2);
+/// assert_eq!(runs.get(), 1);
+/// system.set(&flag, false);
+/// assert_eq!(*system.get(&a), 40);
+/// system.set(&x, 2);
+/// assert_eq!(*system.get(&a), 40);
+/// assert_eq!(runs.get(), 2);
+/// ```
+pub struct FastVal<'n, T> {
+    val: BoxedVal<'n, T>,
+    /// The dependencies read by the last run of the closure.
+    deps: SeenDeps<'n>,
+}
+
+impl<'n, T: 'n> FastVal<'n, T> {
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn new<F: FnMut(&mut FastDeps<'n>, Update<T>) + 'n>(system: &System, mut f: F) -> Self {
+        let deps = SeenDeps::default();
+        let seen = deps.clone();
+        let val = Val::new(
+            system,
+            Box::new(move |u: Update<T>| {
+                let mut deps = FastDeps { deps: Vec::new() };
+                f(&mut deps, u);
+                *seen.borrow_mut() = deps.deps;
+            }) as Box<dyn FnMut(Update<T>) + 'n>,
+        );
+        Self { val, deps }
+    }
+
+    /// The system version when this node was created.
+    pub fn created_version(&self) -> SystemVersion {
+        self.val.created_version()
+    }
+
+    /// Whether the last run read dependencies and none of them advanced since.
+    fn deps_unchanged(&self, system: &System) -> bool {
+        let deps = self.deps.borrow();
+        !deps.is_empty()
+            && deps
+                .iter()
+                .all(|&(dep, version)| dep.version(system) <= version)
+    }
+}
+
+impl<'n, T: 'n> SystemNode for FastVal<'n, T> {
+    type Value = T;
+
+    fn node_id(&self) -> NodeId {
+        self.val.node_id()
+    }
+
+    fn system_id(&self) -> SystemId {
+        self.val.system_id()
+    }
+
+    fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
+        match self.try_get_value(system) {
+            Some(value) => value,
+            None => LazyCatchError::Aborted.raise(),
+        }
+    }
+
+    fn try_get_value<'s>(&'s self, system: &'s System) -> Option<(SystemVersion, &'s Self::Value)> {
+        if !self.val.is_current(system) && self.val.is_computed() && self.deps_unchanged(system) {
+            self.val.mark_current(system);
+        }
+        self.val.try_get_value(system)
+    }
+
+    fn is_computed(&self) -> bool {
+        self.val.is_computed()
+    }
+
+    fn is_current(&self, system: &System) -> bool {
+        self.val.is_current(system)
+    }
+
+    #[cfg(feature = "trace")]
+    fn last_deps(&self) -> Vec<(NodeId, SystemVersion)> {
+        self.val.last_deps()
+    }
+
+    fn tag(&self) -> Option<Tag> {
+        self.val.tag()
+    }
+}
@@ -0,0 +1,144 @@
+use std::cell::UnsafeCell;
+use std::ops::{Deref, DerefMut};
+
+use crate::var::Var;
+use crate::{NodeId, System, SystemId, SystemModify, SystemNode, SystemVersion};
+
+impl System {
+    /// A new system for speculative changes, starting at the version of this one.
+    ///
+    /// The fork borrows this system, so it can not be modified while the fork lives.
+    /// Vars of the parent are brought into the fork with [`Fork::var`], sharing the value
+    /// of the parent until they are modified in the fork.
+    /// Derived nodes are built again on the fork, they are not shared.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// let system = System::new();
+    /// let items = system.var(vec![1, 2, 3]);
+    /// let price = system.var(10);
+    ///
+    /// let mut fork = system.fork();
+    /// let items = fork.var(&items);
+    /// let price = fork.var(&price);
+    /// let total = fork.val(|mut u: Update<i32>| {
+    ///     let count = u.get(&items).len() as i32;
+    ///     let price = *u.get(&price);
+    ///     u.update(|| count * price);
+    /// });
+    /// assert_eq!(*fork.get(&total), 30);
+    /// assert!(!items.is_diverged());
+    /// items.modify(&mut fork.modify()).push(4);
+    /// assert_eq!(*fork.get(&total), 40);
+    /// assert!(items.is_diverged());
+    /// assert!(!price.is_diverged());
+    /// ```
+    pub fn fork(&self) -> Fork<'_> {
+        let mut system = System::new();
+        system.version = self.version;
+        system.coalesce_version = self.version;
+        Fork {
+            parent: self,
+            system,
+        }
+    }
+}
+
+/// See [`System::fork`].
+#[derive(Debug)]
+pub struct Fork<'p> {
+    parent: &'p System,
+    system: System,
+}
+
+impl<'p> Fork<'p> {
+    /// The system this fork was made from.
+    pub fn parent(&self) -> &'p System {
+        self.parent
+    }
+
+    /// Bring a var of the parent into the fork.
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn var<T>(&self, var: &'p Var<T>) -> ForkVar<'p, T> {
+        var.system_id().check_system(self.parent);
+        ForkVar {
+            system_id: self.system.id(),
+            node_id: NodeId::new(),
+            created_version: self.system.version(),
+            parent: self.parent,
+            var,
+            value: UnsafeCell::new(None),
+        }
+    }
+}
+
+impl Deref for Fork<'_> {
+    type Target = System;
+
+    fn deref(&self) -> &Self::Target {
+        &self.system
+    }
+}
+
+impl DerefMut for Fork<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.system
+    }
+}
+
+/// A var of a [`Fork`], reading the value of the parent until it is modified.
+///
+/// The versions of the parent are all older than the fork, so a node of the fork sees the
+/// first modify here as a change like any other.
+pub struct ForkVar<'p, T> {
+    system_id: SystemId,
+    node_id: NodeId,
+    created_version: SystemVersion,
+    parent: &'p System,
+    var: &'p Var<T>,
+    value: UnsafeCell<Option<(SystemVersion, T)>>,
+}
+
+impl<T> ForkVar<'_, T> {
+    /// The system version when this node was created.
+    pub fn created_version(&self) -> SystemVersion {
+        self.created_version
+    }
+
+    /// Whether the var was modified in the fork, and holds its own value.
+    pub fn is_diverged(&self) -> bool {
+        unsafe { &*self.value.get() }.is_some()
+    }
+}
+
+impl<T: Clone> ForkVar<'_, T> {
+    /// Change the value in the fork, cloning the value of the parent at the first change.
+    pub fn modify<'s>(&'s self, modify: &'s mut SystemModify) -> &'s mut T {
+        self.system_id.check_modify(modify);
+        let value = unsafe { &mut *self.value.get() };
+        let (version, value) = value
+            .get_or_insert_with(|| (modify.version(), self.var.get_value(self.parent).1.clone()));
+        *version = modify.write_version(self.node_id);
+        value
+    }
+}
+
+impl<T> SystemNode for ForkVar<'_, T> {
+    type Value = T;
+
+    fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    fn system_id(&self) -> SystemId {
+        self.system_id
+    }
+
+    fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
+        self.system_id.check_system(system);
+        match unsafe { &*self.value.get() } {
+            Some((version, value)) => (*version, value),
+            None => self.var.get_value(self.parent),
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::{System, SystemVersion};
+
+impl System {
+    /// Stop nodes from recomputing until the guard drops.
+    ///
+    /// While frozen, a node that has a value returns it even if stale,
+    /// and a node that never computed computes once.
+    /// After the guard drops the next read recomputes as usual, including the nodes first
+    /// computed while frozen.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let a = system.val(|mut u: Update<i32>| {
+    ///     let v = *u.get(&x);
+    ///     u.update(|| v * 2);
+    /// });
+    /// assert_eq!(*system.get(&a), 2);
+    ///
+    /// let mut frozen = system.freeze();
+    /// *x.modify(&mut frozen.modify()) = 2;
+    /// *x.modify(&mut frozen.modify()) = 3;
+    /// assert_eq!(*frozen.get(&a), 2);
+    /// let b = frozen.val(|mut u: Update<i32>| {
+    ///     let v = *u.get(&x);
+    ///     u.update(|| v);
+    /// });
+    /// assert_eq!(*frozen.get(&b), 3);
+    /// drop(frozen);
+    ///
+    /// assert_eq!(*system.get(&a), 6);
+    /// ```
+    ///
+    /// A node first read while frozen may see stale values, it is checked again after the thaw.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let a = system.val(|mut u: Update<i32>| {
+    ///     let v = *u.get(&x);
+    ///     u.update(|| v * 2);
+    /// });
+    /// assert_eq!(*system.get(&a), 2);
+    ///
+    /// let mut frozen = system.freeze();
+    /// *x.modify(&mut frozen.modify()) = 3;
+    /// let b = frozen.val(|mut u: Update<i32>| {
+    ///     let v = *u.get(&a);
+    ///     u.update(|| v + 100);
+    /// });
+    /// assert_eq!(*frozen.get(&b), 102);
+    /// drop(frozen);
+    ///
+    /// assert_eq!(*system.get(&b), 106);
+    /// ```
+    pub fn freeze(&mut self) -> FreezeGuard<'_> {
+        let was_frozen = std::mem::replace(&mut self.frozen, true);
+        let frozen_version = self.version;
+        FreezeGuard {
+            system: self,
+            was_frozen,
+            frozen_version,
+        }
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+}
+
+#[derive(Debug)]
+pub struct FreezeGuard<'s> {
+    system: &'s mut System,
+    was_frozen: bool,
+    /// The version when the freeze started.
+    frozen_version: SystemVersion,
+}
+
+impl<'s> Deref for FreezeGuard<'s> {
+    type Target = System;
+
+    fn deref(&self) -> &Self::Target {
+        self.system
+    }
+}
+
+impl<'s> DerefMut for FreezeGuard<'s> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.system
+    }
+}
+
+impl<'s> Drop for FreezeGuard<'s> {
+    fn drop(&mut self) {
+        self.system.frozen = self.was_frozen;
+        // Values computed while frozen are stored at the current version, and may have read
+        // stale values. A new version makes the next reads check them again.
+        if !self.was_frozen && self.system.version != self.frozen_version {
+            self.system.version.inc(self.system.overflow_policy);
+        }
+    }
+}
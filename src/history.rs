@@ -0,0 +1,357 @@
+use std::cell::UnsafeCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::val::Val;
+use crate::{NodeId, System, SystemId, SystemModify, SystemNode, SystemVersion, Update};
+
+impl System {
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn history_var<T>(&self, value: T, depth: usize) -> HistoryVar<T> {
+        HistoryVar::new(self, value, depth)
+    }
+
+    /// A history var whose retained values count against the budget of [`System::set_history_budget`].
+    ///
+    /// `size_hint` reports the bytes held by a value, it is called once per value.
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn sized_history_var<T: Send>(
+        &self,
+        value: T,
+        depth: usize,
+        size_hint: fn(&T) -> usize,
+    ) -> HistoryVar<T> {
+        HistoryVar::with_size_hint(self, value, depth, size_hint)
+    }
+
+    /// Bound the bytes retained by all sized history vars, `None` for no bound.
+    ///
+    /// When a set goes over the budget, the oldest values across all sized history vars are
+    /// evicted until it fits again. The newest value of a var is never evicted, so the budget
+    /// can be exceeded by the current values alone. Evicted values are dropped by the set or
+    /// the call of this function that evicted them, and eviction does not advance the version
+    /// of their var: nodes computed from [`HistoryVar::history`] keep their values until the var
+    /// is set again.
+    ///
+    /// ```
+    /// # use lazy_catch::System;
+    /// let mut system = System::new();
+    /// system.set_history_budget(Some(10));
+    /// let a = system.sized_history_var(String::from("aaaa"), 8, String::len);
+    /// let b = system.sized_history_var(String::from("bbbb"), 8, String::len);
+    /// a.set(&mut system.modify(), String::from("cccc"));
+    /// assert_eq!(a.history(&system), ["cccc"]);
+    /// b.set(&mut system.modify(), String::from("dd"));
+    /// b.set(&mut system.modify(), String::from("eeee"));
+    /// assert_eq!(a.history(&system), ["cccc"]);
+    /// assert_eq!(b.history(&system), ["dd", "eeee"]);
+    /// assert_eq!(system.history_bytes(), 10);
+    /// ```
+    pub fn set_history_budget(&mut self, bytes: Option<usize>) {
+        let mut ledger = self.history_ledger.lock().unwrap();
+        ledger.budget = bytes;
+        ledger.evict();
+    }
+
+    /// The bytes retained by all sized history vars, as reported by their size hints.
+    pub fn history_bytes(&self) -> usize {
+        self.history_ledger.lock().unwrap().used
+    }
+
+    /// Aggregate the last `window` values of `node`, or all of them while there are fewer.
+    ///
+    /// ```
+    /// # use lazy_catch::System;
+    /// let mut system = System::new();
+    /// let x = system.history_var(2, 8);
+    /// let average = system.windowed(&x, 2, |values: &[i32]| {
+    ///     values.iter().sum::<i32>() / values.len() as i32
+    /// });
+    /// assert_eq!(*system.get(&average), 2);
+    /// x.set(&mut system.modify(), 4);
+    /// assert_eq!(*system.get(&average), 3);
+    /// x.set(&mut system.modify(), 10);
+    /// assert_eq!(*system.get(&average), 7);
+    /// ```
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn windowed<'n, T, U, F: FnMut(&[T]) -> U + 'n>(
+        &self,
+        node: &'n HistoryVar<T>,
+        window: usize,
+        mut f: F,
+    ) -> Val<U, impl FnMut(Update<U>) + 'n> {
+        self.val(move |mut u: Update<U>| {
+            let history = u.get_history(node);
+            let value = f(&history[history.len().saturating_sub(window)..]);
+            u.update(|| value);
+        })
+    }
+}
+
+/// The values retained by sized history vars, shared by a [`System`] and its vars.
+#[derive(Debug, Default)]
+pub(crate) struct HistoryLedger {
+    budget: Option<usize>,
+    used: usize,
+    /// The node and size of every retained value, oldest first.
+    entries: VecDeque<(NodeId, usize)>,
+    /// The number of entries of each node.
+    live: BTreeMap<NodeId, usize>,
+    /// The storage of each var, to drop the values evicted from it.
+    vars: BTreeMap<NodeId, LedgerVar>,
+}
+
+/// The storage of a sized history var and how to drop its oldest values.
+#[derive(Debug)]
+struct LedgerVar {
+    storage: *const (),
+    drain: unsafe fn(*const (), usize),
+}
+
+// The storage is only drained in a modify or with `&mut System`, when no other thread can
+// read the var, and `sized_history_var` requires `T: Send` for the values dropped.
+unsafe impl Send for LedgerVar {}
+
+impl HistoryLedger {
+    fn push(&mut self, node_id: NodeId, size: usize) {
+        self.used += size;
+        self.entries.push_back((node_id, size));
+        *self.live.entry(node_id).or_default() += 1;
+    }
+
+    /// Forget the oldest entry of a node.
+    fn pop(&mut self, node_id: NodeId) {
+        if let Some(index) = self.entries.iter().position(|&(id, _)| id == node_id) {
+            self.remove(index);
+        }
+    }
+
+    fn remove(&mut self, index: usize) -> NodeId {
+        let (node_id, size) = self.entries.remove(index).unwrap();
+        self.used -= size;
+        *self.live.get_mut(&node_id).unwrap() -= 1;
+        node_id
+    }
+
+    /// Evict the oldest values until the budget fits, keeping the newest value of each node,
+    /// and drop them from their vars.
+    ///
+    /// Only call in a modify or with `&mut System`, so references from reads can not be alive.
+    fn evict(&mut self) {
+        let Some(budget) = self.budget else {
+            return;
+        };
+        let mut evicted = BTreeMap::<NodeId, usize>::new();
+        while self.used > budget {
+            let Some(index) = self.entries.iter().position(|(id, _)| self.live[id] > 1) else {
+                break;
+            };
+            let node_id = self.remove(index);
+            *evicted.entry(node_id).or_default() += 1;
+        }
+        for (node_id, count) in evicted {
+            let var = &self.vars[&node_id];
+            unsafe { (var.drain)(var.storage, count) };
+        }
+    }
+
+    fn forget(&mut self, node_id: NodeId) {
+        while self.live.get(&node_id).is_some_and(|&live| live > 0) {
+            self.pop(node_id);
+        }
+        self.live.remove(&node_id);
+        self.vars.remove(&node_id);
+    }
+}
+
+type SizeHint<T> = (fn(&T) -> usize, Arc<Mutex<HistoryLedger>>);
+
+type HistoryCell<T> = UnsafeCell<(SystemVersion, History<T>)>;
+
+/// The values of a [`HistoryVar`], kept in one slice.
+///
+/// The values popped beyond the depth stay in front of `start` and are dropped together once
+/// there are `depth` of them, so a set moves no values.
+struct History<T> {
+    start: usize,
+    values: Vec<T>,
+}
+
+impl<T> History<T> {
+    fn len(&self) -> usize {
+        self.values.len() - self.start
+    }
+
+    fn as_slice(&self) -> &[T] {
+        &self.values[self.start..]
+    }
+
+    fn push(&mut self, value: T) {
+        self.values.push(value);
+    }
+
+    fn pop_front(&mut self, depth: usize) {
+        self.start += 1;
+        if self.start >= depth {
+            self.compact();
+        }
+    }
+
+    /// Drop the popped values.
+    fn compact(&mut self) {
+        self.values.drain(..self.start);
+        self.start = 0;
+    }
+}
+
+/// Drop the oldest `count` values of the history behind `storage`.
+unsafe fn drain_oldest<T>(storage: *const (), count: usize) {
+    let (_, history) = unsafe { &mut *(*storage.cast::<HistoryCell<T>>()).get() };
+    // The ledger frees memory, drop the drained values now.
+    history.start += count;
+    history.compact();
+}
+
+/// A var that keeps its last `depth` values, oldest first.
+pub struct HistoryVar<T> {
+    system_id: SystemId,
+    node_id: NodeId,
+    created_version: SystemVersion,
+    depth: usize,
+    size_hint: Option<SizeHint<T>>,
+    /// Boxed so that the ledger can drain it after the var moved.
+    value: Box<HistoryCell<T>>,
+}
+
+unsafe impl<T: Sync + Send> Sync for HistoryVar<T> {}
+
+impl<T> HistoryVar<T> {
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn new(system: &System, value: T, depth: usize) -> Self {
+        assert!(depth > 0, "history depth must not be zero");
+        let mut history = History {
+            start: 0,
+            values: Vec::with_capacity(depth),
+        };
+        history.push(value);
+        Self {
+            system_id: system.id(),
+            node_id: NodeId::new(),
+            created_version: system.version(),
+            depth,
+            size_hint: None,
+            value: Box::new(UnsafeCell::new((system.version(), history))),
+        }
+    }
+
+    /// See [`System::sized_history_var`].
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn with_size_hint(
+        system: &System,
+        value: T,
+        depth: usize,
+        size_hint: fn(&T) -> usize,
+    ) -> Self
+    where
+        T: Send,
+    {
+        let mut var = Self::new(system, value, depth);
+        let ledger = system.history_ledger.clone();
+        {
+            let mut ledger = ledger.lock().unwrap();
+            ledger.push(var.node_id, size_hint(&var.value.get_mut().1.as_slice()[0]));
+            let storage: *const HistoryCell<T> = &*var.value;
+            ledger.vars.insert(
+                var.node_id,
+                LedgerVar {
+                    storage: storage.cast(),
+                    drain: drain_oldest::<T>,
+                },
+            );
+        }
+        var.size_hint = Some((size_hint, ledger));
+        var
+    }
+
+    /// The system version when this node was created.
+    pub fn created_version(&self) -> SystemVersion {
+        self.created_version
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Push a new value, dropping the oldest one beyond the depth.
+    pub fn set(&self, modify: &mut SystemModify, value: T) {
+        self.system_id.check_modify(modify);
+        let size = self
+            .size_hint
+            .as_ref()
+            .map(|(size_hint, _)| size_hint(&value));
+        {
+            let (version, history) = unsafe { &mut *self.value.get() };
+            *version = modify.write_version(self.node_id);
+            if history.len() == self.depth {
+                history.pop_front(self.depth);
+                if let Some((_, ledger)) = &self.size_hint {
+                    ledger.lock().unwrap().pop(self.node_id);
+                }
+            }
+            history.push(value);
+        }
+        if let (Some(size), Some((_, ledger))) = (size, &self.size_hint) {
+            let mut ledger = ledger.lock().unwrap();
+            ledger.push(self.node_id, size);
+            // May drain this var too, the history is no longer borrowed.
+            ledger.evict();
+        }
+    }
+
+    /// The retained values, oldest first.
+    pub fn history<'s>(&'s self, system: &'s System) -> &'s [T] {
+        self.get_history(system).1
+    }
+
+    fn get_history<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s [T]) {
+        self.system_id.check_system(system);
+        let (version, history) = unsafe { &*self.value.get() };
+        (*version, history.as_slice())
+    }
+}
+
+impl<T> Drop for HistoryVar<T> {
+    fn drop(&mut self) {
+        if let Some((_, ledger)) = &self.size_hint {
+            // Always forget the storage, the ledger must not drain it after it is freed.
+            let mut ledger = ledger.lock().unwrap_or_else(PoisonError::into_inner);
+            ledger.forget(self.node_id);
+        }
+    }
+}
+
+impl<T> SystemNode for HistoryVar<T> {
+    type Value = T;
+
+    fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    fn system_id(&self) -> SystemId {
+        self.system_id
+    }
+
+    fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
+        let (version, history) = self.get_history(system);
+        (version, history.last().unwrap())
+    }
+}
+
+impl<'s, T> Update<'s, T> {
+    /// Read the retained values of a [`HistoryVar`], oldest first.
+    pub fn get_history<'r, U>(&'r mut self, node: &'r HistoryVar<U>) -> &'r [U] {
+        let (version, history) = node.get_history(self.system);
+        self.track_node(node.node_id(), version);
+        history
+    }
+}
@@ -0,0 +1,126 @@
+use std::cell::{Cell, UnsafeCell};
+
+use crate::error::LazyCatchError;
+use crate::{NodeId, System, SystemId, SystemNode, SystemVersion};
+
+impl System {
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn latch<'n, N: SystemNode + ?Sized>(&self, node: &'n N) -> Latch<'n, N, N::Value>
+    where
+        N::Value: Clone,
+    {
+        Latch::new(self, node)
+    }
+}
+
+/// A copy of a node taken at its first read, that ignores its changes until [`Latch::release`].
+///
+/// The copy keeps the version of the value it was taken from.
+/// A release takes effect at the next read at a version the latch was not read at yet,
+/// values handed out at the current version stay valid until the next modify.
+///
+/// ```
+/// # use lazy_catch::System;
+/// let mut system = System::new();
+/// let x = system.var(1);
+/// let latch = system.latch(&x);
+/// assert_eq!(*system.get(&latch), 1);
+/// system.set(&x, 2);
+/// assert_eq!(*system.get(&latch), 1);
+/// latch.release();
+/// system.set(&x, 3);
+/// assert_eq!(*system.get(&latch), 3);
+/// system.set(&x, 4);
+/// assert_eq!(*system.get(&latch), 3);
+/// ```
+pub struct Latch<'n, N: ?Sized, T> {
+    system_id: SystemId,
+    node_id: NodeId,
+    created_version: SystemVersion,
+    node: &'n N,
+    released: Cell<bool>,
+    check_version: Cell<Option<SystemVersion>>,
+    value: UnsafeCell<Option<(SystemVersion, T)>>,
+}
+
+impl<'n, N: SystemNode<Value = T> + ?Sized, T: Clone> Latch<'n, N, T> {
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn new(system: &System, node: &'n N) -> Self {
+        Self {
+            system_id: system.id(),
+            node_id: NodeId::new(),
+            created_version: system.version(),
+            node,
+            released: Cell::new(false),
+            check_version: Cell::new(None),
+            value: UnsafeCell::new(None),
+        }
+    }
+
+    /// The system version when this node was created.
+    pub fn created_version(&self) -> SystemVersion {
+        self.created_version
+    }
+
+    /// Take a new copy of the node at the next read.
+    pub fn release(&self) {
+        self.released.set(true);
+    }
+
+    /// Keep the current copy, cancelling a release that has not taken effect yet.
+    pub fn hold(&self) {
+        self.released.set(false);
+    }
+}
+
+impl<N: SystemNode<Value = T> + ?Sized, T: Clone> SystemNode for Latch<'_, N, T> {
+    type Value = T;
+
+    fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    fn system_id(&self) -> SystemId {
+        self.system_id
+    }
+
+    fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
+        match self.try_get_value(system) {
+            Some(value) => value,
+            None => LazyCatchError::Aborted.raise(),
+        }
+    }
+
+    fn try_get_value<'s>(&'s self, system: &'s System) -> Option<(SystemVersion, &'s Self::Value)> {
+        self.system_id.check_system(system);
+        system.record_get();
+        if self.check_version.get() != Some(system.version()) {
+            // Values handed out at an older version can not be alive, a modify borrowed the system.
+            let value = unsafe { &mut *self.value.get() };
+            if value.is_none() || self.released.replace(false) {
+                match self.node.try_get_value(system) {
+                    Some((version, upstream)) => {
+                        if value.as_ref().map(|&(v, _)| v) != Some(version) {
+                            *value = Some((version, upstream.clone()));
+                        }
+                    }
+                    // Keep the copy, and take one at the next version.
+                    None => self.released.set(true),
+                }
+            }
+            if value.is_some() {
+                self.check_version.set(Some(system.version()));
+            }
+        }
+        let (version, value) = unsafe { &*self.value.get() }.as_ref()?;
+        Some((*version, value))
+    }
+
+    fn is_computed(&self) -> bool {
+        self.check_version.get().is_some()
+    }
+
+    fn is_current(&self, system: &System) -> bool {
+        self.check_version.get() == Some(system.version())
+    }
+}
@@ -21,18 +21,145 @@
 //! });
 //! let mut modify = system.modify();
 //! *arc_x.modify(&mut modify) = 10;
+//! drop(modify);
 //!
 //! std::thread::spawn(move || {
 //!     assert_eq!(*system.get(&b), 12);
 //! }).join().unwrap();
 //! ```
 //!
+//! # Laziness
+//!
+//! An update closure only runs when its node is read, directly or through a node that depends
+//! on it. Modifying vars, reading other nodes and pulling the nodes of
+//! [`System::eager_val`] never runs it.
+//!
+//! ```
+//! # use std::cell::Cell;
+//! # use std::sync::Arc;
+//! # use lazy_catch::{System, Update};
+//! let mut system = System::new();
+//! let x = system.var(1);
+//! let other = Arc::new(system.var(1));
+//! let runs = Cell::new(0);
+//! let leaf = system.val(|mut u: Update<i32>| {
+//!     runs.set(runs.get() + 1);
+//!     let x = *u.get(&x);
+//!     u.update(|| x);
+//! });
+//! let unrelated = system.val(|mut u: Update<i32>| {
+//!     let other = *u.get(&*other);
+//!     u.update(|| other);
+//! });
+//! let eager = system.eager_val({
+//!     let other = other.clone();
+//!     move |mut u: Update<i32>| {
+//!         let other = *u.get(&*other);
+//!         u.update(|| other);
+//!     }
+//! });
+//! for n in 0..10 {
+//!     system.set(&x, n);
+//!     system.set(&other, n);
+//!     system.get(&unrelated);
+//!     system.get(&*eager);
+//! }
+//! assert_eq!(runs.get(), 0);
+//! assert_eq!(*system.get(&leaf), 9);
+//! assert_eq!(runs.get(), 1);
+//! ```
+//!
+//! # Dynamic dependencies
+//!
+//! The dependencies of a node are the nodes its last run read, and each run starts a new set.
+//! A node that stops reading another one no longer changes with it, and a newly read node
+//! counts as changed, even if its version is older than the stored value.
+//!
+//! ```
+//! # use std::cell::Cell;
+//! # use lazy_catch::{System, SystemNode, Update};
+//! let mut system = System::new();
+//! let use_a = system.var(true);
+//! let a = system.var(1);
+//! let b = system.var(10);
+//! let stored = Cell::new(0);
+//! let pick = system.val(|mut u: Update<i32>| {
+//!     let v = if *u.get(&use_a) { *u.get(&a) } else { *u.get(&b) };
+//!     u.update(|| {
+//!         stored.set(stored.get() + 1);
+//!         v
+//!     });
+//! });
+//! assert_eq!(*system.get(&pick), 1);
+//! system.set(&b, 20);
+//! assert_eq!(*system.get(&pick), 1);
+//! assert_eq!(stored.get(), 1);
+//!
+//! system.set(&use_a, false);
+//! assert_eq!(*system.get(&pick), 20);
+//! assert_eq!(stored.get(), 2);
+//! system.set(&a, 2);
+//! assert_eq!(*system.get(&pick), 20);
+//! assert_eq!(stored.get(), 2);
+//! assert_eq!(system.get_versioned(&pick).0, use_a.get_value(&system).0);
+//! system.set(&b, 30);
+//! assert_eq!(*system.get(&pick), 30);
+//!
+//! system.set(&use_a, true);
+//! assert_eq!(*system.get(&pick), 2);
+//! assert_eq!(stored.get(), 4);
+//! ```
+//!
 
+mod access;
+pub mod arena;
+pub mod atomic;
+pub mod combinator;
+pub mod drop_val;
+pub mod eager;
+pub mod error;
+pub mod fast;
+pub mod fork;
+pub mod freeze;
+pub mod history;
+pub mod latch;
+#[macro_use]
+mod macros;
+pub mod manual;
+pub mod map_var;
+pub mod observer;
+pub mod ops;
+pub mod optional;
+pub mod param;
+pub mod pipe;
+pub mod profile;
+#[cfg(feature = "testing")]
+pub mod recording;
+mod registry;
+pub mod runner;
+pub mod stale;
+pub mod stats;
+mod trace;
 pub mod val;
+pub mod validate;
 pub mod var;
+pub mod version;
+pub mod view;
 
+use std::any::Any;
+use std::cell::Cell;
+use std::collections::BTreeMap;
+#[cfg(feature = "trace")]
+use std::collections::BTreeSet;
 use std::num::NonZeroU64;
+use std::ops::Deref;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::rc::Rc;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::error::LazyCatchError;
+use crate::registry::NodeRecord;
 
 /// ```
 /// # use lazy_catch::System;
@@ -54,35 +181,106 @@ impl SystemId {
     }
 
     pub fn check_system(&self, system: &System) {
-        assert_eq!(*self, system.id());
+        if *self != system.id() {
+            LazyCatchError::WrongSystem.raise();
+        }
     }
 
     pub fn check_modify(&self, modify: &SystemModify) {
-        assert_eq!(*self, modify.id());
+        if *self != modify.id() {
+            LazyCatchError::WrongSystem.raise();
+        }
     }
 }
 
+/// Identity of a node, unique in the process and increasing in construction order.
+///
+/// ```
+/// # use lazy_catch::{System, SystemNode};
+/// let system = System::new();
+/// let a = system.var(0);
+/// let b = system.var(0);
+/// assert_eq!(a.node_id(), a.node_id());
+/// assert!(a.node_id() < b.node_id());
+/// ```
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct NodeId {
+    id: u64,
+}
+
+impl NodeId {
+    pub(crate) fn new() -> Self {
+        static ID: AtomicU64 = AtomicU64::new(0);
+        let id = ID.fetch_add(1, Ordering::Relaxed);
+        Self { id }
+    }
+}
+
+/// A user defined category of nodes, for tooling that groups them without downcasting.
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct Tag(pub u32);
+
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub struct SystemVersion {
     pub(crate) version: NonZeroU64,
 }
 
 impl SystemVersion {
+    /// The version as a number, increasing with every modify.
+    pub fn get(&self) -> NonZeroU64 {
+        self.version
+    }
+
     pub(crate) fn new() -> Self {
         Self {
             version: NonZeroU64::new(1).unwrap(),
         }
     }
 
-    pub(crate) fn inc(&mut self) {
-        self.version = self.version.checked_add(1).unwrap();
+    pub(crate) fn inc(&mut self, policy: OverflowPolicy) {
+        self.version = match (self.version.checked_add(1), policy) {
+            (Some(version), _) => version,
+            (None, OverflowPolicy::Panic) => LazyCatchError::VersionExhausted.raise(),
+            (None, OverflowPolicy::Saturate) => self.version,
+        };
     }
 }
 
+/// What [`System::modify`] does once the version reached its maximum,
+/// see [`System::set_overflow_policy`].
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub enum OverflowPolicy {
+    /// Fail with [`LazyCatchError::VersionExhausted`].
+    #[default]
+    Panic,
+    /// Keep the maximum version: writes are stamped with it, but nodes that were checked at
+    /// it never see them, so the graph stays as it was.
+    Saturate,
+}
+
 #[derive(Debug)]
 pub struct System {
     id: SystemId,
     version: SystemVersion,
+    coalesce_version: SystemVersion,
+    overflow_policy: OverflowPolicy,
+    frozen: bool,
+    #[cfg(feature = "stats")]
+    stats: stats::StatsCounter,
+    registry: Mutex<BTreeMap<NodeId, Weak<NodeRecord>>>,
+    eager: eager::EagerNodes,
+    history_ledger: Arc<Mutex<history::HistoryLedger>>,
+    commit_callbacks: observer::CommitCallbacks,
+    /// The panic of an eager node or a commit callback run by the drop of a modify.
+    commit_panic: Mutex<Option<Box<dyn Any + Send>>>,
+    #[cfg(feature = "trace")]
+    constants: BTreeSet<NodeId>,
+    /// The dependencies read by sealed sync vals, debug builds panic when one is written.
+    #[cfg(feature = "trace")]
+    sealed_deps: Mutex<BTreeSet<NodeId>>,
+    /// The runs of update closures of each node, see [`recording::RecordingSystem`].
+    #[cfg(feature = "testing")]
+    recording: Option<Mutex<BTreeMap<NodeId, u64>>>,
 }
 
 impl Default for System {
@@ -105,9 +303,51 @@ impl System {
         Self {
             id: SystemId::new(),
             version: SystemVersion::new(),
+            coalesce_version: SystemVersion::new(),
+            overflow_policy: OverflowPolicy::Panic,
+            frozen: false,
+            #[cfg(feature = "stats")]
+            stats: Default::default(),
+            registry: Mutex::new(BTreeMap::new()),
+            eager: Default::default(),
+            history_ledger: Default::default(),
+            commit_callbacks: Default::default(),
+            commit_panic: Mutex::new(None),
+            #[cfg(feature = "trace")]
+            constants: BTreeSet::new(),
+            #[cfg(feature = "trace")]
+            sealed_deps: Mutex::new(BTreeSet::new()),
+            #[cfg(feature = "testing")]
+            recording: None,
         }
     }
 
+    /// A system whose version starts at `version`, to test behavior near the limits.
+    ///
+    /// ```
+    /// # use std::num::NonZeroU64;
+    /// # use std::panic::{catch_unwind, AssertUnwindSafe};
+    /// # use lazy_catch::System;
+    /// # use lazy_catch::error::LazyCatchError;
+    /// let mut system = System::with_version(NonZeroU64::new(u64::MAX - 1).unwrap());
+    /// system.modify();
+    /// let payload = catch_unwind(AssertUnwindSafe(|| {
+    ///     system.modify();
+    /// }))
+    /// .unwrap_err();
+    /// assert_eq!(
+    ///     payload.downcast_ref::<LazyCatchError>(),
+    ///     Some(&LazyCatchError::VersionExhausted),
+    /// );
+    /// ```
+    #[cfg(feature = "testing")]
+    pub fn with_version(version: NonZeroU64) -> Self {
+        let mut system = Self::new();
+        system.version = SystemVersion { version };
+        system.coalesce_version = system.version;
+        system
+    }
+
     pub fn id(&self) -> SystemId {
         self.id
     }
@@ -116,26 +356,680 @@ impl System {
         self.version
     }
 
-    pub fn get<'s, N: SystemNode + ?Sized>(&'s self, node: &'s N) -> &'s N::Value {
+    /// Read a node, bringing it up to date.
+    ///
+    /// The borrows of the system and of the node are independent, the value can be kept for
+    /// as long as both are alive.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, SystemNode, Update};
+    /// fn render<N: SystemNode + ?Sized>(system: &System, node: &N) -> String
+    /// where
+    ///     N::Value: ToString,
+    /// {
+    ///     system.get(node).to_string()
+    /// }
+    ///
+    /// fn largest<'r, N: SystemNode<Value = i32>>(system: &'r System, nodes: &'r [N]) -> &'r i32 {
+    ///     nodes.iter().map(|node| system.get(node)).max().unwrap()
+    /// }
+    ///
+    /// fn read_all<'s, 'n, N: SystemNode>(system: &'s System, nodes: &'n [N]) -> Vec<&'n N::Value>
+    /// where
+    ///     's: 'n,
+    /// {
+    ///     nodes.iter().map(|node| system.get(node)).collect()
+    /// }
+    ///
+    /// let system = System::new();
+    /// let xs = [system.var(3), system.var(7)];
+    /// let double = system.val(|mut u: Update<i32>| {
+    ///     let x = *u.get(&xs[0]);
+    ///     u.update(|| x * 2);
+    /// });
+    /// assert_eq!(render(&system, &double), "6");
+    /// assert_eq!(render::<dyn SystemNode<Value = i32>>(&system, &xs[1]), "7");
+    /// assert_eq!(*largest(&system, &xs), 7);
+    /// assert_eq!(read_all(&system, &xs), [&3, &7]);
+    /// ```
+    pub fn get<'r, 's: 'r, 'n: 'r, N: SystemNode + ?Sized>(&'s self, node: &'n N) -> &'r N::Value {
         let (_version, value) = node.get_value(self);
         value
     }
 
-    pub fn modify(&mut self) -> SystemModify {
-        self.version.inc();
-        SystemModify { system: self }
+    /// Read a node with the version of its value, with the same lifetimes as [`System::get`].
+    pub fn get_versioned<'r, 's: 'r, 'n: 'r, N: SystemNode + ?Sized>(
+        &'s self,
+        node: &'n N,
+    ) -> (SystemVersion, &'r N::Value) {
+        node.get_value(self)
+    }
+
+    /// Read a node only if it already holds a value, `None` instead of its first update.
+    ///
+    /// A node that was computed before is brought up to date as by [`System::get`].
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// let system = System::new();
+    /// let x = system.var(1);
+    /// let a = system.val(|u: Update<i32>| u.update(|| 2));
+    /// assert_eq!(system.get_if_computed(&x), Some(&1));
+    /// assert_eq!(system.get_if_computed(&a), None);
+    /// system.get(&a);
+    /// assert_eq!(system.get_if_computed(&a), Some(&2));
+    /// ```
+    pub fn get_if_computed<'s, N: SystemNode + ?Sized>(
+        &'s self,
+        node: &'s N,
+    ) -> Option<&'s N::Value> {
+        node.is_computed().then(|| self.get(node))
+    }
+
+    /// The version of the value of `node` if it is current, `None` if only a recompute can tell.
+    ///
+    /// This never runs an update, so it is cheap while the system version stays the same.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let a = system.val(|mut u: Update<i32>| {
+    ///     let x = *u.get(&x);
+    ///     u.update(|| x + 1);
+    /// });
+    /// assert_eq!(system.value_version_if_current(&a), None);
+    /// let version = system.get_versioned(&a).0;
+    /// assert_eq!(system.value_version_if_current(&a), Some(version));
+    /// system.invalidate();
+    /// assert_eq!(system.value_version_if_current(&a), None);
+    /// assert_eq!(system.value_version_if_current(&x), Some(system.get_versioned(&x).0));
+    /// ```
+    pub fn value_version_if_current<N: SystemNode + ?Sized>(
+        &self,
+        node: &N,
+    ) -> Option<SystemVersion> {
+        node.is_current(self).then(|| node.get_value(self).0)
+    }
+
+    /// Read a node, or fail if it belongs to a different system, or if its update aborted
+    /// without a previous value, see [`Update::abort`].
+    ///
+    /// ```
+    /// # use lazy_catch::System;
+    /// # use lazy_catch::error::LazyCatchError;
+    /// let system = System::new();
+    /// let other = System::new();
+    /// let x = other.var(0);
+    /// assert_eq!(system.try_get(&x), Err(LazyCatchError::WrongSystem));
+    /// assert_eq!(other.try_get(&x), Ok(&0));
+    /// ```
+    pub fn try_get<'s, N: SystemNode + ?Sized>(
+        &'s self,
+        node: &'s N,
+    ) -> Result<&'s N::Value, LazyCatchError> {
+        if node.system_id() != self.id() {
+            return Err(LazyCatchError::WrongSystem);
+        }
+        match node.try_get_value(self) {
+            Some((_version, value)) => Ok(value),
+            None => Err(LazyCatchError::Aborted),
+        }
+    }
+
+    /// Read a node with the nodes its last update read and the versions it saw,
+    /// ordered by node id, for cache keys that change when any input changed.
+    ///
+    /// Only nodes are listed, see [`SystemNode::last_deps`].
+    ///
+    /// ```
+    /// # use lazy_catch::{System, SystemNode, Update};
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let y = system.var(2);
+    /// let sum = system.val(|mut u: Update<i32>| {
+    ///     let v = *u.get(&x) + *u.get(&y) + *u.get(&x);
+    ///     u.update(|| v);
+    /// });
+    /// let (value, deps) = system.get_with_deps(&sum);
+    /// assert_eq!(*value, 4);
+    /// let versions = |system: &System| vec![
+    ///     (x.node_id(), x.get_value(system).0),
+    ///     (y.node_id(), y.get_value(system).0),
+    /// ];
+    /// assert_eq!(deps, versions(&system));
+    /// system.set(&y, 3);
+    /// assert_eq!(system.get_with_deps(&sum).1, versions(&system));
+    /// ```
+    #[cfg(feature = "trace")]
+    pub fn get_with_deps<'s, N: SystemNode + ?Sized>(
+        &'s self,
+        node: &'s N,
+    ) -> (&'s N::Value, Vec<(NodeId, SystemVersion)>) {
+        let value = self.get(node);
+        (value, node.last_deps())
+    }
+
+    /// Read an element of a node holding a sequence, `None` if `index` is out of bounds.
+    ///
+    /// ```
+    /// # use lazy_catch::System;
+    /// let system = System::new();
+    /// let xs = system.var(vec![1, 2, 3]);
+    /// assert_eq!(system.get_index(&xs, 1), Some(&2));
+    /// assert_eq!(system.get_index(&xs, 3), None);
+    /// ```
+    pub fn get_index<'s, E, N: SystemNode + ?Sized>(
+        &'s self,
+        node: &'s N,
+        index: usize,
+    ) -> Option<&'s E>
+    where
+        N::Value: Deref<Target = [E]>,
+    {
+        self.get(node).get(index)
+    }
+
+    /// A closure returning a clone of the current value of `node`, for callback based APIs.
+    ///
+    /// Each call reads the node like [`System::get`], running its update if it is stale.
+    /// The closure borrows both the system and the node, so no modify can happen while it lives;
+    /// see [`System::shared_getter`] for a `'static` closure.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// fn call_twice(f: impl Fn() -> i32) -> i32 {
+    ///     f() + f()
+    /// }
+    ///
+    /// let system = System::new();
+    /// let x = system.var(3);
+    /// let double = system.val(|mut u: Update<i32>| {
+    ///     let x = *u.get(&x);
+    ///     u.update(|| x * 2);
+    /// });
+    /// assert_eq!(call_twice(system.getter(&double)), 12);
+    /// ```
+    pub fn getter<'s, N: SystemNode + ?Sized>(&'s self, node: &'s N) -> impl Fn() -> N::Value + 's
+    where
+        N::Value: Clone,
+    {
+        move || self.get(node).clone()
+    }
+
+    /// A `'static` closure returning a clone of the current value of `node`.
+    ///
+    /// The closure owns a share of both, so the system can only be modified again through
+    /// [`Arc::get_mut`] once every closure is dropped.
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use lazy_catch::System;
+    /// let mut system = Arc::new(System::new());
+    /// let x = Arc::new(system.var(1));
+    /// let get: Box<dyn Fn() -> i32> = Box::new(System::shared_getter(&system, &x));
+    /// assert_eq!(get(), 1);
+    /// drop(get);
+    /// Arc::get_mut(&mut system).unwrap().set(&x, 2);
+    /// assert_eq!(*system.get(&*x), 2);
+    /// ```
+    pub fn shared_getter<N: SystemNode + ?Sized + 'static>(
+        system: &Arc<Self>,
+        node: &Arc<N>,
+    ) -> impl Fn() -> N::Value + 'static
+    where
+        N::Value: Clone,
+    {
+        let system = system.clone();
+        let node = node.clone();
+        move || system.get(&*node).clone()
+    }
+
+    /// Read `node` and let `copy` write its value into `buf`, to reuse a buffer instead of
+    /// cloning the value.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let x = system.var(1_u8);
+    /// let frame = system.val(|mut u: Update<Vec<u8>>| {
+    ///     let x = *u.get(&x);
+    ///     u.update(|| vec![x; 4]);
+    /// });
+    /// let mut buf = Vec::with_capacity(16);
+    /// let copy = |frame: &Vec<u8>, buf: &mut Vec<u8>| {
+    ///     buf.clear();
+    ///     buf.extend_from_slice(frame);
+    /// };
+    /// system.read_into(&frame, &mut buf, copy);
+    /// assert_eq!(buf, [1; 4]);
+    /// let capacity = buf.capacity();
+    /// system.set(&x, 2);
+    /// system.read_into(&frame, &mut buf, copy);
+    /// assert_eq!(buf, [2; 4]);
+    /// assert_eq!(buf.capacity(), capacity);
+    /// ```
+    pub fn read_into<N: SystemNode + ?Sized, B: ?Sized>(
+        &self,
+        node: &N,
+        buf: &mut B,
+        copy: impl FnOnce(&N::Value, &mut B),
+    ) {
+        copy(self.get(node), buf);
+    }
+
+    /// Choose what [`System::modify`] does once the version reached its maximum.
+    ///
+    /// ```
+    /// # use std::num::NonZeroU64;
+    /// # use lazy_catch::{OverflowPolicy, System};
+    /// let mut system = System::new();
+    /// system.modify_at(NonZeroU64::MAX);
+    /// system.set_overflow_policy(OverflowPolicy::Saturate);
+    /// system.modify();
+    /// assert_eq!(system.version().get(), NonZeroU64::MAX);
+    /// ```
+    pub fn set_overflow_policy(&mut self, policy: OverflowPolicy) {
+        self.overflow_policy = policy;
+    }
+
+    pub fn modify(&mut self) -> SystemModify<'_> {
+        let previous_version = self.version;
+        self.version.inc(self.overflow_policy);
+        SystemModify {
+            system: self,
+            previous_version,
+            written: false,
+            finished: false,
+        }
+    }
+
+    /// Start a modify at `version`, to keep the system in step with an external clock
+    /// such as a database transaction id.
+    ///
+    /// The version must be newer than the current one, or this fails with
+    /// [`LazyCatchError::VersionNotIncreasing`]. Gaps are fine, nodes only compare versions,
+    /// and a plain [`System::modify`] continues from the new version.
+    ///
+    /// ```
+    /// # use std::num::NonZeroU64;
+    /// # use std::panic::{catch_unwind, AssertUnwindSafe};
+    /// # use lazy_catch::System;
+    /// # use lazy_catch::error::LazyCatchError;
+    /// let mut system = System::new();
+    /// let balance = system.var(0);
+    /// let transaction = NonZeroU64::new(1042).unwrap();
+    /// *balance.modify(&mut system.modify_at(transaction)) = 10;
+    /// assert_eq!(system.version().get(), transaction);
+    /// assert_eq!(system.get_versioned(&balance), (system.version(), &10));
+    ///
+    /// let payload = catch_unwind(AssertUnwindSafe(|| {
+    ///     system.modify_at(NonZeroU64::new(1000).unwrap());
+    /// }))
+    /// .unwrap_err();
+    /// assert_eq!(
+    ///     payload.downcast_ref::<LazyCatchError>(),
+    ///     Some(&LazyCatchError::VersionNotIncreasing),
+    /// );
+    /// ```
+    pub fn modify_at(&mut self, version: NonZeroU64) -> SystemModify<'_> {
+        if version <= self.version.version {
+            LazyCatchError::VersionNotIncreasing.raise();
+        }
+        let previous_version = self.version;
+        self.version = SystemVersion { version };
+        SystemModify {
+            system: self,
+            previous_version,
+            written: false,
+            finished: false,
+        }
+    }
+
+    /// The panic of an eager node or a commit callback run when a modify dropped, if any.
+    ///
+    /// Dropping a [`SystemModify`] never panics, [`SystemModify::commit`] raises such a panic
+    /// instead.
+    ///
+    /// ```
+    /// # use std::panic::{catch_unwind, AssertUnwindSafe};
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let _check = system.eager_val(|u: Update<i32>| {
+    ///     if u.system().version().get().get() > 1 {
+    ///         panic!("check failed");
+    ///     }
+    ///     u.update(|| 0);
+    /// });
+    /// system.set(&x, 2);
+    /// let panic = system.take_commit_panic().unwrap();
+    /// assert_eq!(panic.downcast_ref::<&str>(), Some(&"check failed"));
+    /// assert!(system.take_commit_panic().is_none());
+    ///
+    /// let mut modify = system.modify();
+    /// *x.modify(&mut modify) = 3;
+    /// catch_unwind(AssertUnwindSafe(|| modify.commit())).unwrap_err();
+    /// assert!(system.take_commit_panic().is_none());
+    /// ```
+    pub fn take_commit_panic(&mut self) -> Option<Box<dyn Any + Send>> {
+        self.commit_panic.get_mut().unwrap().take()
+    }
+
+    /// Jump to `version` without a modify, as if a modify that wrote nothing ran at it.
+    ///
+    /// The version must be newer than the current one, or this fails with
+    /// [`LazyCatchError::VersionNotIncreasing`].
+    #[cfg(feature = "testing")]
+    pub fn set_version(&mut self, version: SystemVersion) {
+        if version <= self.version {
+            LazyCatchError::VersionNotIncreasing.raise();
+        }
+        self.version = version;
+    }
+
+    /// Run `apply` in one modify at each of the recorded `versions`, to drive the system
+    /// through a recorded session with the same versions as the original run.
+    ///
+    /// `apply` gets the index of the version and the modify. The versions must be strictly
+    /// increasing and newer than the current one, which is checked before any of them is
+    /// applied, failing with [`LazyCatchError::VersionNotIncreasing`].
+    ///
+    /// ```
+    /// # use lazy_catch::{System, SystemNode, Update};
+    /// let mut system = System::new();
+    /// let x = system.var(0);
+    /// let mut recorded = Vec::new();
+    /// for n in 1..=3 {
+    ///     system.modify();
+    ///     system.set(&x, n);
+    ///     recorded.push(x.get_value(&system).0);
+    /// }
+    ///
+    /// let mut replayed = System::new();
+    /// let x = replayed.var(0);
+    /// let double = replayed.val(|mut u: Update<i32>| {
+    ///     let x = *u.get(&x);
+    ///     u.update(|| x * 2);
+    /// });
+    /// replayed.replay(&recorded, |index, modify| *x.modify(modify) = index as i32 + 1);
+    /// assert_eq!(replayed.version(), recorded[2]);
+    /// assert_eq!(replayed.get_versioned(&double), (recorded[2], &6));
+    /// ```
+    pub fn replay(
+        &mut self,
+        versions: &[SystemVersion],
+        mut apply: impl FnMut(usize, &mut SystemModify),
+    ) {
+        let mut previous = self.version;
+        for &version in versions {
+            if version <= previous {
+                LazyCatchError::VersionNotIncreasing.raise();
+            }
+            previous = version;
+        }
+        for (index, &version) in versions.iter().enumerate() {
+            apply(index, &mut self.modify_at(version.version));
+        }
+    }
+
+    /// Advance the version without changing any node, the same as a `modify` that writes nothing.
+    ///
+    /// Every node checks its dependencies again at its next read, and keeps its value and
+    /// version if none of them advanced.
+    ///
+    /// ```
+    /// # use std::cell::Cell;
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let runs = Cell::new(0);
+    /// let a = system.val(|mut u: Update<i32>| {
+    ///     runs.set(runs.get() + 1);
+    ///     let x = *u.get(&x);
+    ///     u.update(|| x * 2);
+    /// });
+    /// let before = system.get_versioned(&a);
+    /// let before = (before.0, *before.1);
+    /// system.invalidate();
+    /// let after = system.get_versioned(&a);
+    /// assert_eq!((after.0, *after.1), before);
+    /// assert_eq!(runs.get(), 2);
+    /// ```
+    pub fn invalidate(&mut self) {
+        self.modify();
     }
 }
 
 pub trait SystemNode {
     type Value: ?Sized;
 
+    fn node_id(&self) -> NodeId;
+
+    fn system_id(&self) -> SystemId;
+
+    /// Whether both refer to the same underlying node, through any reference or pointer.
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use lazy_catch::{System, SystemNode};
+    /// let system = System::new();
+    /// let x = Arc::new(system.var(0));
+    /// let y = system.var(0);
+    /// let nodes: Vec<&dyn SystemNode<Value = i32>> = vec![&x, &*x, &y];
+    /// assert!(nodes[0].same_node(nodes[1]));
+    /// assert!(!nodes[0].same_node(nodes[2]));
+    /// ```
+    fn same_node(&self, other: &dyn SystemNode<Value = Self::Value>) -> bool {
+        self.node_id() == other.node_id()
+    }
+
     fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value);
+
+    /// Like [`SystemNode::get_value`], but `None` instead of failing with
+    /// [`LazyCatchError::Aborted`] when the update aborted without a previous value,
+    /// see [`Update::abort`].
+    ///
+    /// Nodes wrapping an update, or reading another node for their value, forward its abort.
+    ///
+    /// ```
+    /// # use lazy_catch::error::LazyCatchError;
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let ready = system.var(false);
+    /// let a = system.val(|mut u: Update<i32>| {
+    ///     if !*u.get(&ready) {
+    ///         return u.abort();
+    ///     }
+    ///     u.update(|| 1);
+    /// });
+    /// let latch = system.latch(&a);
+    /// let fast = system.fast_val(|deps, mut u: Update<i32>| {
+    ///     if !*deps.get(&mut u, &ready) {
+    ///         return u.abort();
+    ///     }
+    ///     u.update(|| 2);
+    /// });
+    /// assert_eq!(system.try_get(&latch), Err(LazyCatchError::Aborted));
+    /// assert_eq!(system.try_get(&fast), Err(LazyCatchError::Aborted));
+    /// system.set(&ready, true);
+    /// assert_eq!(system.try_get(&latch), Ok(&1));
+    /// assert_eq!(system.try_get(&fast), Ok(&2));
+    /// ```
+    fn try_get_value<'s>(&'s self, system: &'s System) -> Option<(SystemVersion, &'s Self::Value)> {
+        Some(self.get_value(system))
+    }
+
+    /// Whether the node holds a value, so reading it runs no update for the first time.
+    ///
+    /// Nodes that always hold a value, like [`var::Var`], keep the default of `true`.
+    fn is_computed(&self) -> bool {
+        true
+    }
+
+    /// Whether the node was checked at the current version of `system`, so reading it runs no update.
+    ///
+    /// Nodes that are never stale, like [`var::Var`], keep the default of `true`.
+    fn is_current(&self, system: &System) -> bool {
+        let _ = system;
+        true
+    }
+
+    /// The category given to the node at construction, see [`System::tagged_val`].
+    fn tag(&self) -> Option<Tag> {
+        None
+    }
+
+    /// The nodes read by the last run of the update of this node, with the versions it saw,
+    /// ordered by node id. Empty for nodes without an update, see [`System::get_with_deps`].
+    #[cfg(feature = "trace")]
+    fn last_deps(&self) -> Vec<(NodeId, SystemVersion)> {
+        Vec::new()
+    }
 }
 
+impl<N: SystemNode + ?Sized> SystemNode for &N {
+    type Value = N::Value;
+
+    fn node_id(&self) -> NodeId {
+        (**self).node_id()
+    }
+
+    fn system_id(&self) -> SystemId {
+        (**self).system_id()
+    }
+
+    fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
+        (**self).get_value(system)
+    }
+
+    fn try_get_value<'s>(&'s self, system: &'s System) -> Option<(SystemVersion, &'s Self::Value)> {
+        (**self).try_get_value(system)
+    }
+
+    fn is_computed(&self) -> bool {
+        (**self).is_computed()
+    }
+
+    fn is_current(&self, system: &System) -> bool {
+        (**self).is_current(system)
+    }
+
+    #[cfg(feature = "trace")]
+    fn last_deps(&self) -> Vec<(NodeId, SystemVersion)> {
+        (**self).last_deps()
+    }
+
+    fn tag(&self) -> Option<Tag> {
+        (**self).tag()
+    }
+}
+
+impl<N: SystemNode + ?Sized> SystemNode for Rc<N> {
+    type Value = N::Value;
+
+    fn node_id(&self) -> NodeId {
+        (**self).node_id()
+    }
+
+    fn system_id(&self) -> SystemId {
+        (**self).system_id()
+    }
+
+    fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
+        (**self).get_value(system)
+    }
+
+    fn try_get_value<'s>(&'s self, system: &'s System) -> Option<(SystemVersion, &'s Self::Value)> {
+        (**self).try_get_value(system)
+    }
+
+    fn is_computed(&self) -> bool {
+        (**self).is_computed()
+    }
+
+    fn is_current(&self, system: &System) -> bool {
+        (**self).is_current(system)
+    }
+
+    #[cfg(feature = "trace")]
+    fn last_deps(&self) -> Vec<(NodeId, SystemVersion)> {
+        (**self).last_deps()
+    }
+
+    fn tag(&self) -> Option<Tag> {
+        (**self).tag()
+    }
+}
+
+impl<N: SystemNode + ?Sized> SystemNode for Arc<N> {
+    type Value = N::Value;
+
+    fn node_id(&self) -> NodeId {
+        (**self).node_id()
+    }
+
+    fn system_id(&self) -> SystemId {
+        (**self).system_id()
+    }
+
+    fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
+        (**self).get_value(system)
+    }
+
+    fn try_get_value<'s>(&'s self, system: &'s System) -> Option<(SystemVersion, &'s Self::Value)> {
+        (**self).try_get_value(system)
+    }
+
+    fn is_computed(&self) -> bool {
+        (**self).is_computed()
+    }
+
+    fn is_current(&self, system: &System) -> bool {
+        (**self).is_current(system)
+    }
+
+    #[cfg(feature = "trace")]
+    fn last_deps(&self) -> Vec<(NodeId, SystemVersion)> {
+        (**self).last_deps()
+    }
+
+    fn tag(&self) -> Option<Tag> {
+        (**self).tag()
+    }
+}
+
+/// A [`SystemNode`] that can be downcast to its concrete type.
+///
+/// ```
+/// # use lazy_catch::{AnySystemNode, System};
+/// # use lazy_catch::var::Var;
+/// let mut system = System::new();
+/// let node: Box<dyn AnySystemNode<Value = i32>> = Box::new(system.var(1));
+/// let x = node.as_any().downcast_ref::<Var<i32>>().unwrap();
+/// *x.modify(&mut system.modify()) = 2;
+/// assert_eq!(*system.get(&*node), 2);
+/// ```
+pub trait AnySystemNode: SystemNode {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<N: SystemNode + Any> AnySystemNode for N {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Write access to the nodes of a system at a new version.
+///
+/// Dropping it brings the nodes of [`System::eager_val`] up to date.
 #[derive(Debug)]
 pub struct SystemModify<'s> {
     system: &'s mut System,
+    previous_version: SystemVersion,
+    written: bool,
+    /// Whether the eager nodes were pulled and the commit callbacks called.
+    finished: bool,
 }
 
 impl<'s> SystemModify<'s> {
@@ -146,15 +1040,124 @@ impl<'s> SystemModify<'s> {
     pub fn version(&self) -> SystemVersion {
         self.system.version()
     }
+
+    /// The version before this modify started.
+    ///
+    /// ```
+    /// # use lazy_catch::System;
+    /// let mut system = System::new();
+    /// let before = system.version();
+    /// let modify = system.modify();
+    /// assert_eq!(modify.previous_version(), before);
+    /// assert!(modify.version() > before);
+    /// ```
+    pub fn previous_version(&self) -> SystemVersion {
+        self.previous_version
+    }
+
+    /// Whether a node was written in this modify.
+    ///
+    /// ```
+    /// # use lazy_catch::System;
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let mut modify = system.modify();
+    /// assert!(!modify.is_written());
+    /// assert!(!x.set_if_changed(&mut modify, 1));
+    /// assert!(!modify.is_written());
+    /// *x.modify(&mut modify) = 2;
+    /// assert!(modify.is_written());
+    /// ```
+    pub fn is_written(&self) -> bool {
+        self.written
+    }
+
+    /// End the modify, pulling the eager nodes and calling the commit callbacks.
+    ///
+    /// A panic of one of them raises here. Dropping the modify does the same work, but keeps
+    /// such a panic for [`System::take_commit_panic`] instead.
+    pub fn commit(mut self) {
+        self.finish();
+    }
+
+    fn finish(&mut self) {
+        if std::mem::replace(&mut self.finished, true) {
+            return;
+        }
+        self.system.pull_eager();
+        if self.written {
+            let version = self.version();
+            self.system.commit_callbacks.call(version);
+        }
+    }
+
+    /// The version to stamp on `node_id` written in this modify.
+    pub(crate) fn write_version(&mut self, node_id: NodeId) -> SystemVersion {
+        #[cfg(feature = "trace")]
+        if self.system.constants.contains(&node_id) {
+            LazyCatchError::ConstantWrite.raise();
+        }
+        #[cfg(feature = "trace")]
+        debug_assert!(
+            !self.system.sealed_deps.lock().unwrap().contains(&node_id),
+            "dependency of a sealed SyncVal written"
+        );
+        #[cfg(not(feature = "trace"))]
+        let _ = node_id;
+        self.written = true;
+        self.version()
+    }
+
+    /// Whether a node was written so far, forgetting it so that the end of this modify calls
+    /// no commit callback for those writes.
+    pub(crate) fn take_written(&mut self) -> bool {
+        std::mem::take(&mut self.written)
+    }
+}
+
+impl Drop for SystemModify<'_> {
+    fn drop(&mut self) {
+        // A modify left by a panic runs no user code, the eager nodes update at their next read.
+        if std::thread::panicking() {
+            return;
+        }
+        if let Err(panic) = catch_unwind(AssertUnwindSafe(|| self.finish())) {
+            *self.system.commit_panic.get_mut().unwrap() = Some(panic);
+        }
+    }
 }
 
+/// What [`Update::update_outcome`] did with the value.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum UpdateOutcome {
+    /// A dependency advanced, the new value was stored.
+    Stored,
+    /// No dependency advanced past the stored value, the closure of `update` did not run.
+    SkippedUnchanged,
+    /// The new value was equal to the old one, see [`System::val_eq_by`].
+    SkippedEqual,
+    /// No dependency was read, so the value was stored at the current version,
+    /// as it is at every check of such a node.
+    NoDeps,
+}
+
+#[must_use = "call `update`, `update_with_old` or `keep` to finish the update"]
 pub struct Update<'s, T> {
     system: &'s System,
     current_version: Option<SystemVersion>,
     update_version: Option<SystemVersion>,
     receiver: &'s mut Option<(SystemVersion, T)>,
+    #[cfg(feature = "trace")]
+    trace: Option<(&'s trace::NodeTrace, Option<NodeId>)>,
+    eq: Option<EqHook<'s, T>>,
+    on_replace: Option<&'s dyn Fn(&T)>,
+    /// Set by [`Update::abort`].
+    aborted: Option<&'s Cell<bool>>,
 }
 
+/// The comparator of [`System::val_eq_by`], with the newest dependency version it computed at.
+type EqHook<'s, T> = (&'s dyn Fn(&T, &T) -> bool, &'s Cell<Option<SystemVersion>>);
+
 impl<'s, T> Update<'s, T> {
     pub fn new(
         system: &'s System,
@@ -166,6 +1169,11 @@ impl<'s, T> Update<'s, T> {
             current_version,
             update_version: None,
             receiver,
+            #[cfg(feature = "trace")]
+            trace: None,
+            eq: None,
+            on_replace: None,
+            aborted: None,
         }
     }
 
@@ -173,8 +1181,202 @@ impl<'s, T> Update<'s, T> {
         self.system
     }
 
-    pub fn get<'r, N: SystemNode + ?Sized>(&'r mut self, node: &'r N) -> &'r N::Value {
+    /// Read a node and depend on it.
+    ///
+    /// Reading the same node again in one run gives the same value and version.
+    /// The value does not borrow the update, so values of several nodes can be held at once.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, SystemNode, Update};
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let y = system.var(0);
+    /// let a = system.val(|mut u: Update<i32>| {
+    ///     let first = *u.get(&x);
+    ///     let second = *u.get(&x);
+    ///     assert_eq!(first, second);
+    ///     u.update(|| first + second);
+    /// });
+    /// *x.modify(&mut system.modify()) = 2;
+    /// *y.modify(&mut system.modify()) = 1;
+    /// assert_eq!(a.get_value(&system), (x.get_value(&system).0, &4));
+    /// ```
+    pub fn get<'r, N: SystemNode + ?Sized>(&mut self, node: &'r N) -> &'r N::Value
+    where
+        's: 'r,
+    {
+        match self.try_get(node) {
+            Ok(value) => value,
+            Err(err) => err.raise(),
+        }
+    }
+
+    /// Read a node with the version of its value and depend on it.
+    pub fn get_versioned<'r, N: SystemNode + ?Sized>(
+        &mut self,
+        node: &'r N,
+    ) -> (SystemVersion, &'r N::Value)
+    where
+        's: 'r,
+    {
         let (version, value) = node.get_value(self.system);
+        self.track_node(node.node_id(), version);
+        (version, value)
+    }
+
+    /// Read a node, depend on it and return whether its value advanced since the value of this
+    /// node was stored, so a closure can recompute only the parts that depend on it.
+    ///
+    /// Every node counts as changed when there is no value yet, or it must be stored again.
+    ///
+    /// ```
+    /// # use std::cell::Cell;
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let text = system.var(String::from("lazy"));
+    /// let scale = system.var(2);
+    /// let runs = Cell::new(0);
+    /// let mut words = 0;
+    /// let score = system.val(|mut u: Update<usize>| {
+    ///     if u.changed(&text) {
+    ///         runs.set(runs.get() + 1);
+    ///         words = u.get(&text).split_whitespace().count();
+    ///     }
+    ///     let scale = *u.get(&scale);
+    ///     u.update(|| words * scale);
+    /// });
+    /// assert_eq!(*system.get(&score), 2);
+    /// system.set(&scale, 3);
+    /// assert_eq!(*system.get(&score), 3);
+    /// assert_eq!(runs.get(), 1);
+    /// system.set(&text, String::from("lazy catch"));
+    /// assert_eq!(*system.get(&score), 6);
+    /// assert_eq!(runs.get(), 2);
+    /// ```
+    pub fn changed<N: SystemNode + ?Sized>(&mut self, node: &N) -> bool {
+        let (version, _) = self.get_versioned(node);
+        self.current_version < Some(version)
+    }
+
+    /// Read an element of a node holding a sequence and depend on the whole sequence,
+    /// `None` if `index` is out of bounds.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let xs = system.var(vec![1, 2, 3]);
+    /// let last = system.var(2);
+    /// let a = system.val(|mut u: Update<Option<i32>>| {
+    ///     let index = *u.get(&last);
+    ///     let x = u.get_index(&xs, index).copied();
+    ///     u.update(|| x);
+    /// });
+    /// assert_eq!(*system.get(&a), Some(3));
+    /// system.modify_var(&xs, |xs| xs.truncate(2));
+    /// assert_eq!(*system.get(&a), None);
+    /// ```
+    pub fn get_index<'r, E, N: SystemNode + ?Sized>(
+        &mut self,
+        node: &'r N,
+        index: usize,
+    ) -> Option<&'r E>
+    where
+        's: 'r,
+        N::Value: Deref<Target = [E]>,
+    {
+        self.get(node).get(index)
+    }
+
+    /// Read a node, depend on it and return a projection of its value.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let person = system.var((String::from("Ada"), 36));
+    /// let a = system.val(|mut u: Update<String>| {
+    ///     let name = u.map_get(&person, |(name, _)| name.clone());
+    ///     let age = u.map_get(&person, |&(_, age)| age);
+    ///     u.update(|| format!("{name} {age}"));
+    /// });
+    /// assert_eq!(system.get(&a), "Ada 36");
+    /// system.modify_var(&person, |(_, age)| *age += 1);
+    /// assert_eq!(system.get(&a), "Ada 37");
+    /// ```
+    pub fn map_get<N: SystemNode + ?Sized, U, F: FnOnce(&N::Value) -> U>(
+        &mut self,
+        node: &N,
+        f: F,
+    ) -> U {
+        f(self.get(node))
+    }
+
+    /// Read a node, or fail if it belongs to a different system.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// # use lazy_catch::error::LazyCatchError;
+    /// let system = System::new();
+    /// let other = System::new();
+    /// let x = other.var(0);
+    /// let a = system.val(|mut u: Update<Result<i32, LazyCatchError>>| {
+    ///     let v = u.try_get(&x).copied();
+    ///     u.update(|| v);
+    /// });
+    /// assert_eq!(*system.get(&a), Err(LazyCatchError::WrongSystem));
+    /// ```
+    pub fn try_get<'r, N: SystemNode + ?Sized>(
+        &mut self,
+        node: &'r N,
+    ) -> Result<&'r N::Value, LazyCatchError>
+    where
+        's: 'r,
+    {
+        if node.system_id() != self.system.id() {
+            return Err(LazyCatchError::WrongSystem);
+        }
+        let (version, value) = node.get_value(self.system);
+        self.track_node(node.node_id(), version);
+        Ok(value)
+    }
+
+    /// Depend on the system version itself, so the value is stored again after every modify.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let frames = system.val(|mut u: Update<u64>| {
+    ///     u.depend_on_version();
+    ///     u.update_with_old(|old| old.map_or(0, |frame| frame + 1));
+    /// });
+    /// assert_eq!(*system.get(&frames), 0);
+    /// system.modify();
+    /// system.modify();
+    /// assert_eq!(*system.get(&frames), 1);
+    /// system.modify();
+    /// assert_eq!(*system.get(&frames), 2);
+    /// ```
+    pub fn depend_on_version(&mut self) {
+        #[cfg(feature = "trace")]
+        if let Some((trace, _)) = &self.trace {
+            trace.set_on_version();
+        }
+        self.track(self.system.version());
+    }
+
+    pub(crate) fn track_node(&mut self, node_id: NodeId, version: SystemVersion) {
+        #[cfg(feature = "trace")]
+        if let Some((trace, trigger)) = &mut self.trace {
+            trace.push_dep(node_id, version);
+            if self.update_version < Some(version) {
+                *trigger = Some(node_id);
+            }
+        }
+        #[cfg(not(feature = "trace"))]
+        let _ = node_id;
+        self.track(version);
+    }
+
+    pub(crate) fn track(&mut self, version: SystemVersion) {
         if let Some(old) = self.update_version {
             if old < version {
                 self.update_version = Some(version);
@@ -182,17 +1384,126 @@ impl<'s, T> Update<'s, T> {
         } else {
             self.update_version = Some(version);
         }
-        value
     }
 
     pub fn update<F: FnOnce() -> T>(self, f: F) {
+        self.update_outcome(f);
+    }
+
+    /// Like [`Update::update`], reporting whether the value was stored and why not.
+    ///
+    /// ```
+    /// # use std::cell::Cell;
+    /// # use lazy_catch::{System, Update, UpdateOutcome};
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let outcome = Cell::new(None);
+    /// let a = system.val_eq(|mut u: Update<i32>| {
+    ///     let x = *u.get(&x);
+    ///     outcome.set(Some(u.update_outcome(|| x / 10)));
+    /// });
+    /// system.get(&a);
+    /// assert_eq!(outcome.get(), Some(UpdateOutcome::Stored));
+    /// system.modify();
+    /// system.get(&a);
+    /// assert_eq!(outcome.get(), Some(UpdateOutcome::SkippedUnchanged));
+    /// system.set(&x, 2);
+    /// system.get(&a);
+    /// assert_eq!(outcome.get(), Some(UpdateOutcome::SkippedEqual));
+    ///
+    /// let b = system.val(|u: Update<i32>| outcome.set(Some(u.update_outcome(|| 0))));
+    /// system.get(&b);
+    /// system.modify();
+    /// system.get(&b);
+    /// assert_eq!(outcome.get(), Some(UpdateOutcome::NoDeps));
+    /// ```
+    pub fn update_outcome<F: FnOnce() -> T>(self, f: F) -> UpdateOutcome {
+        let outcome = match self.update_version {
+            Some(_) => UpdateOutcome::Stored,
+            None => UpdateOutcome::NoDeps,
+        };
         let update_version = self.update_version.unwrap_or(self.system().version());
         if let Some(current_version) = self.current_version {
             if update_version <= current_version {
-                return;
+                return UpdateOutcome::SkippedUnchanged;
+            }
+        }
+        let value = f();
+        if let Some((eq, computed)) = self.eq {
+            computed.set(Some(update_version));
+            if let Some((_, old)) = self.receiver.as_ref() {
+                if eq(old, &value) {
+                    return UpdateOutcome::SkippedEqual;
+                }
             }
         }
-        *self.receiver = Some((update_version, f()));
+        if let (Some(on_replace), Some((_, old))) = (self.on_replace, self.receiver.as_ref()) {
+            on_replace(old);
+        }
+        self.record_trigger();
+        *self.receiver = Some((update_version, value));
+        outcome
+    }
+
+    #[inline]
+    fn record_trigger(&self) {
+        #[cfg(feature = "trace")]
+        if let Some((trace, trigger)) = self.trace {
+            trace.set(trigger);
+        }
+    }
+
+    /// Keep the current value without computing a new one.
+    pub fn keep(self) {}
+
+    /// Give up this update without storing anything, because the value can not be computed
+    /// right now.
+    ///
+    /// With a previous value, reads give it, stale, until the next modify, whose first read runs
+    /// the update again. A value handed out is never replaced at the version it was read at.
+    /// Without a previous value the node is not marked as checked, so each read runs the update
+    /// again, and fails with [`LazyCatchError::Aborted`] if it aborts, which
+    /// [`System::try_get`] returns as an error.
+    ///
+    /// # Panics
+    ///
+    /// For an update made with [`Update::new`], which has no node to leave unchecked.
+    ///
+    /// ```
+    /// # use std::panic::{catch_unwind, AssertUnwindSafe};
+    /// # use lazy_catch::error::LazyCatchError;
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let connected = system.var(false);
+    /// let data = system.var(1);
+    /// let remote = system.val(|mut u: Update<i32>| {
+    ///     if !*u.get(&connected) {
+    ///         return u.abort();
+    ///     }
+    ///     let data = *u.get(&data);
+    ///     u.update(|| data * 10);
+    /// });
+    /// assert_eq!(system.try_get(&remote), Err(LazyCatchError::Aborted));
+    /// let payload = catch_unwind(AssertUnwindSafe(|| *system.get(&remote))).unwrap_err();
+    /// assert_eq!(
+    ///     payload.downcast_ref::<LazyCatchError>(),
+    ///     Some(&LazyCatchError::Aborted),
+    /// );
+    ///
+    /// system.set(&connected, true);
+    /// assert_eq!(system.try_get(&remote), Ok(&10));
+    /// system.set(&connected, false);
+    /// system.set(&data, 2);
+    /// let stale = system.get(&remote);
+    /// assert_eq!((*stale, *system.get(&remote)), (10, 10));
+    /// system.set(&connected, true);
+    /// assert_eq!(*system.get(&remote), 20);
+    /// ```
+    pub fn abort(self) {
+        match self.aborted {
+            Some(aborted) => aborted.set(true),
+            None => panic!("Update::abort on an update made with Update::new"),
+        }
     }
 
     pub fn update_with_old<F: FnOnce(Option<T>) -> T>(self, f: F) {
@@ -202,7 +1513,36 @@ impl<'s, T> Update<'s, T> {
                 return;
             }
         }
+        self.record_trigger();
         let old = self.receiver.take().map(|(_, v)| v);
         *self.receiver = Some((update_version, f(old)));
     }
+
+    /// Like [`Update::update_with_old`], also passing the version the old value was stored at.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, SystemVersion, Update};
+    /// let mut system = System::new();
+    /// let x = system.var(0);
+    /// let a = system.val(|mut u: Update<(Option<SystemVersion>, i32)>| {
+    ///     let x = *u.get(&x);
+    ///     u.update_with_old_versioned(|old| (old.map(|(version, _)| version), x));
+    /// });
+    /// assert_eq!(*system.get(&a), (None, 0));
+    /// let stored = system.get_versioned(&a).0;
+    /// system.set(&x, 1);
+    /// assert_eq!(*system.get(&a), (Some(stored), 1));
+    /// assert!(system.get_versioned(&a).0 > stored);
+    /// ```
+    pub fn update_with_old_versioned<F: FnOnce(Option<(SystemVersion, T)>) -> T>(self, f: F) {
+        let update_version = self.update_version.unwrap_or(self.system().version());
+        if let Some(current_version) = self.current_version {
+            if update_version <= current_version {
+                return;
+            }
+        }
+        self.record_trigger();
+        let old = self.receiver.take();
+        *self.receiver = Some((update_version, f(old)));
+    }
 }
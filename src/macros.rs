@@ -0,0 +1,169 @@
+/// Read several nodes with the same value type and give `(index, &value)` of the newest one.
+///
+/// Every node is read, so any needed recompute happens. On equal versions the lowest index wins.
+///
+/// ```
+/// # use lazy_catch::{select_newest, System};
+/// let mut system = System::new();
+/// let a = system.var(1);
+/// let b = system.var(2);
+/// let c = system.var(3);
+/// assert_eq!(select_newest!(system, [a, b, c]), (0, &1));
+/// *b.modify(&mut system.modify()) = 20;
+/// assert_eq!(select_newest!(system, [a, b, c]), (1, &20));
+/// ```
+#[macro_export]
+macro_rules! select_newest {
+    ($system:expr, [$($node:expr),+ $(,)?]) => {{
+        let system: &$crate::System = &$system;
+        let mut newest = None;
+        let mut index = 0usize;
+        $(
+            let (version, value) = system.get_versioned(&$node);
+            match newest {
+                Some((_, newest_version, _)) if newest_version >= version => {}
+                _ => newest = Some((index, version, value)),
+            }
+            index += 1;
+        )+
+        let _ = index;
+        let (index, _, value) = newest.unwrap();
+        (index, value)
+    }};
+}
+
+/// Declare vars and the vals derived from them as local bindings.
+///
+/// `let name = var(value);` creates a var. `let name = val(a, b => expr);` creates a val
+/// that reads `a` and `b` as references to their values, depends on them and stores `expr`.
+/// A val can only name nodes declared before it. The nodes are ordinary locals,
+/// vars are modified as usual, for example with [`System::set`](crate::System::set).
+///
+/// ```
+/// # use lazy_catch::{graph, System};
+/// let mut system = System::new();
+/// graph! {
+///     system;
+///     let a = var(1);
+///     let b = val(a => *a + 1);
+///     let c = val(a, b => *a + *b);
+/// }
+/// assert_eq!(*system.get(&c), 3);
+/// system.set(&a, 10);
+/// assert_eq!(*system.get(&b), 11);
+/// assert_eq!(*system.get(&c), 21);
+/// ```
+#[macro_export]
+macro_rules! graph {
+    ($system:expr; $(let $name:ident = $kind:ident($($args:tt)*);)*) => {
+        $($crate::graph!(@node $system, $name, $kind($($args)*));)*
+    };
+    (@node $system:expr, $name:ident, var($value:expr)) => {
+        let $name = $system.var($value);
+    };
+    (@node $system:expr, $name:ident, val($($dep:ident),+ => $body:expr)) => {
+        let $name = $system.val(|mut u: $crate::Update<_>| {
+            $(let $dep = u.get(&$dep);)+
+            u.update(|| $body);
+        });
+    };
+}
+
+/// A val formatting the values of nodes as [`format!`] does, recomputed when one of them changes.
+///
+/// Only the given nodes are read. A result equal to the previous string keeps its version,
+/// as with [`System::val_eq`](crate::System::val_eq).
+///
+/// ```
+/// # use lazy_catch::{format_node, System};
+/// let mut system = System::new();
+/// let label = system.var("count");
+/// let count = system.var(1);
+/// let text = format_node!(system, "{}: {}", label, count);
+/// assert_eq!(system.get(&text), "count: 1");
+/// system.set(&count, 2);
+/// assert_eq!(system.get(&text), "count: 2");
+/// ```
+#[macro_export]
+macro_rules! format_node {
+    ($system:expr, $fmt:literal $(, $node:expr)* $(,)?) => {
+        $system.val_eq(|mut u: $crate::Update<::std::string::String>| {
+            $(u.get(&$node);)*
+            let system = u.system();
+            u.update(|| {
+                ::std::format!($fmt $(, $crate::SystemNode::get_value(&$node, system).1)*)
+            });
+        })
+    };
+}
+
+/// Declare a struct with a constructor of a val holding clones of the values of nodes,
+/// one node per field.
+///
+/// The attributes are kept on the struct, which must implement `PartialEq`, derived or by hand:
+/// the val keeps its version when the new struct is equal to the previous one, as with
+/// [`System::val_eq`](crate::System::val_eq). Every node is a dependency, the values are only
+/// cloned when one of them changed.
+///
+/// ```
+/// # use lazy_catch::{struct_node, System, Update};
+/// struct_node! {
+///     #[derive(Debug, PartialEq)]
+///     pub struct Window {
+///         pub title: String,
+///         pub width: u32,
+///     }
+/// }
+///
+/// let mut system = System::new();
+/// let title = system.var(String::from("main"));
+/// let size = system.var(640);
+/// let width = system.val(|mut u: Update<u32>| {
+///     let size = *u.get(&size);
+///     u.update(|| size.min(800));
+/// });
+/// let window = Window::node(&system, &title, &width);
+/// assert_eq!(
+///     *system.get(&window),
+///     Window { title: String::from("main"), width: 640 },
+/// );
+/// system.set(&size, 1000);
+/// let version = system.get_versioned(&window).0;
+/// system.set(&size, 2000);
+/// assert_eq!(system.get_versioned(&window).0, version);
+/// assert_eq!(system.get(&window).width, 800);
+/// ```
+#[macro_export]
+macro_rules! struct_node {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $($field_vis:vis $field:ident: $ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        $vis struct $name {
+            $($field_vis $field: $ty,)*
+        }
+
+        impl $name {
+            /// A val holding clones of the values of the nodes, one node per field.
+            #[must_use = "nodes must be stored to be read later"]
+            #[allow(clippy::too_many_arguments)]
+            $vis fn node<'n>(
+                system: &$crate::System,
+                $($field: &'n (dyn $crate::SystemNode<Value = $ty> + 'n),)*
+            ) -> $crate::val::Val<Self, impl FnMut($crate::Update<Self>) + 'n> {
+                system.val_eq(move |mut u: $crate::Update<Self>| {
+                    $(u.get($field);)*
+                    let system = u.system();
+                    u.update(|| Self {
+                        $($field: ::std::clone::Clone::clone(
+                            $crate::SystemNode::get_value($field, system).1,
+                        ),)*
+                    });
+                })
+            }
+        }
+    };
+}
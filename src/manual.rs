@@ -0,0 +1,137 @@
+use std::cell::{Cell, UnsafeCell};
+
+use crate::error::LazyCatchError;
+use crate::observer;
+use crate::profile::ProfileSpan;
+use crate::{NodeId, System, SystemId, SystemNode, SystemVersion};
+
+impl System {
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn manual_val<T, F: FnOnce() -> T>(&self, f: F) -> ManualVal<T, F> {
+        ManualVal::new(self, f)
+    }
+}
+
+/// A node that runs its closure once, at its first read, and again only after [`ManualVal::reset`].
+///
+/// The closure reads no dependencies, the value is stored at the system version of the read
+/// that ran it.
+///
+/// ```
+/// # use lazy_catch::System;
+/// let mut system = System::new();
+/// let config = system.manual_val(Box::new(|| String::from("v1")) as Box<dyn FnOnce() -> String>);
+/// assert_eq!(system.get(&config), "v1");
+/// let loaded = system.get_versioned(&config).0;
+/// system.modify();
+/// assert_eq!(system.get_versioned(&config).0, loaded);
+/// let old = system.get(&config);
+/// config.reset(&system, Box::new(|| String::from("v2")));
+/// assert_eq!(system.get(&config), "v1");
+/// assert_eq!(old, "v1");
+/// system.modify();
+/// assert_eq!(system.get(&config), "v2");
+/// assert!(system.get_versioned(&config).0 > loaded);
+/// ```
+pub struct ManualVal<T, F = fn() -> T> {
+    system_id: SystemId,
+    node_id: NodeId,
+    created_version: SystemVersion,
+    lock: Cell<bool>,
+    /// The closure run at the first read.
+    init: Cell<Option<F>>,
+    /// The closure of the last reset, with the version it was armed at.
+    reset: Cell<Option<(SystemVersion, F)>>,
+    value: UnsafeCell<Option<(SystemVersion, T)>>,
+}
+
+impl<T, F: FnOnce() -> T> ManualVal<T, F> {
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn new(system: &System, f: F) -> Self {
+        Self {
+            system_id: system.id(),
+            node_id: NodeId::new(),
+            created_version: system.version(),
+            lock: Cell::new(false),
+            init: Cell::new(Some(f)),
+            reset: Cell::new(None),
+            value: UnsafeCell::new(None),
+        }
+    }
+
+    /// The system version when this node was created.
+    pub fn created_version(&self) -> SystemVersion {
+        self.created_version
+    }
+
+    /// Arm the node with a new closure, run at the first read of a newer system version.
+    ///
+    /// The old value stays until then, so values already handed out at this version stay valid.
+    /// A node that never ran takes the new closure instead of its first one. While the system
+    /// is frozen the old value is kept.
+    pub fn reset(&self, system: &System, f: F) {
+        self.system_id.check_system(system);
+        self.reset.set(Some((system.version(), f)));
+    }
+
+    /// Whether a reset is armed at an older version than `version`.
+    fn reset_due(&self, version: SystemVersion) -> bool {
+        let reset = self.reset.take();
+        let due = reset.as_ref().is_some_and(|&(armed, _)| armed < version);
+        self.reset.set(reset);
+        due
+    }
+}
+
+impl<T, F: FnOnce() -> T> SystemNode for ManualVal<T, F> {
+    type Value = T;
+
+    fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    fn system_id(&self) -> SystemId {
+        self.system_id
+    }
+
+    fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
+        match self.try_get_value(system) {
+            Some(value) => value,
+            None => LazyCatchError::Aborted.raise(),
+        }
+    }
+
+    fn try_get_value<'s>(&'s self, system: &'s System) -> Option<(SystemVersion, &'s Self::Value)> {
+        self.system_id.check_system(system);
+        system.record_get();
+        if self.lock.get() {
+            LazyCatchError::Recursion.raise();
+        }
+        let version = system.version();
+        let stored = unsafe { &*self.value.get() }.is_some();
+        if !stored || (!system.is_frozen() && self.reset_due(version)) {
+            observer::check_not_dispatching();
+            let init = self.init.take();
+            if let Some(f) = self.reset.take().map(|(_, f)| f).or(init) {
+                self.lock.set(true);
+                let span = ProfileSpan::enter(self.node_id);
+                let value = f();
+                span.exit();
+                // Values handed out at an older version can not be alive, a modify borrowed the system.
+                unsafe { *self.value.get() = Some((version, value)) };
+                self.lock.set(false);
+                system.record_run(self.node_id, true);
+            }
+        }
+        let (version, value) = unsafe { &*self.value.get() }.as_ref()?;
+        Some((*version, value))
+    }
+
+    fn is_computed(&self) -> bool {
+        !self.lock.get() && unsafe { &*self.value.get() }.is_some()
+    }
+
+    fn is_current(&self, system: &System) -> bool {
+        !self.lock.get() && self.is_computed() && !self.reset_due(system.version())
+    }
+}
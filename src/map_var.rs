@@ -0,0 +1,244 @@
+use std::borrow::Borrow;
+use std::cell::UnsafeCell;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::{NodeId, System, SystemId, SystemModify, SystemNode, SystemVersion, Update};
+
+impl System {
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn map_var<K: Eq + Hash, V>(&self, map: HashMap<K, V>) -> MapVar<K, V> {
+        MapVar::new(self, map)
+    }
+}
+
+/// The value of a [`MapVar`].
+#[derive(Debug)]
+pub struct MapEntries<K, V> {
+    entries: HashMap<K, (SystemVersion, V)>,
+}
+
+impl<K: Eq + Hash, V> MapEntries<K, V> {
+    pub fn get<Q: Eq + Hash + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.entries.get(key).map(|(_, v)| v)
+    }
+
+    pub fn contains_key<Q: Eq + Hash + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.entries.contains_key(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.entries.keys()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter().map(|(k, (_, v))| (k, v))
+    }
+}
+
+struct MapState<K, V> {
+    version: SystemVersion,
+    structure_version: SystemVersion,
+    entries: MapEntries<K, V>,
+}
+
+/// A map with a version for every key.
+///
+/// Reading a single key with [`Update::get_key`] only depends on that key,
+/// reading the whole map as a [`SystemNode`] depends on every change.
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use lazy_catch::{System, SystemNode, Update};
+/// let mut system = System::new();
+/// let map = system.map_var(HashMap::from([("a", 1), ("b", 2)]));
+/// let a = system.val(|mut u: Update<Option<i32>>| {
+///     let v = u.get_key(&map, "a").copied();
+///     u.update(|| v);
+/// });
+/// assert_eq!(*system.get(&a), Some(1));
+/// let before = a.get_value(&system).0;
+///
+/// map.insert(&mut system.modify(), "b", 3);
+/// assert_eq!(a.get_value(&system).0, before);
+///
+/// *map.modify_key(&mut system.modify(), "a").unwrap() = 10;
+/// assert_eq!(*system.get(&a), Some(10));
+///
+/// map.remove(&mut system.modify(), "a");
+/// assert_eq!(*system.get(&a), None);
+/// ```
+pub struct MapVar<K, V> {
+    system_id: SystemId,
+    node_id: NodeId,
+    created_version: SystemVersion,
+    value: UnsafeCell<MapState<K, V>>,
+}
+
+unsafe impl<K: Sync + Send, V: Sync + Send> Sync for MapVar<K, V> {}
+
+impl<K: Eq + Hash, V> MapVar<K, V> {
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn new(system: &System, map: HashMap<K, V>) -> Self {
+        let version = system.version();
+        Self {
+            system_id: system.id(),
+            node_id: NodeId::new(),
+            created_version: system.version(),
+            value: UnsafeCell::new(MapState {
+                version,
+                structure_version: version,
+                entries: MapEntries {
+                    entries: map.into_iter().map(|(k, v)| (k, (version, v))).collect(),
+                },
+            }),
+        }
+    }
+
+    /// The system version when this node was created.
+    pub fn created_version(&self) -> SystemVersion {
+        self.created_version
+    }
+
+    fn state_mut<'s>(&'s self, modify: &'s mut SystemModify) -> &'s mut MapState<K, V> {
+        self.system_id.check_modify(modify);
+        let state = unsafe { &mut *self.value.get() };
+        state.version = modify.write_version(self.node_id);
+        state
+    }
+
+    fn state(&self, system: &System) -> &MapState<K, V> {
+        self.system_id.check_system(system);
+        unsafe { &*self.value.get() }
+    }
+
+    pub fn insert(&self, modify: &mut SystemModify, key: K, value: V) -> Option<V> {
+        let version = modify.version();
+        let state = self.state_mut(modify);
+        let old = state.entries.entries.insert(key, (version, value));
+        if old.is_none() {
+            state.structure_version = version;
+        }
+        old.map(|(_, v)| v)
+    }
+
+    pub fn remove<Q: Eq + Hash + ?Sized>(&self, modify: &mut SystemModify, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        if !self.contains_key(modify, key) {
+            return None;
+        }
+        let version = modify.version();
+        let state = self.state_mut(modify);
+        state.structure_version = version;
+        state.entries.entries.remove(key).map(|(_, v)| v)
+    }
+
+    /// Edit the value of a key in place, only a present key advances the version of the map.
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// # use lazy_catch::{System, SystemNode};
+    /// let mut system = System::new();
+    /// let map = system.map_var(HashMap::from([("a", 1)]));
+    /// let version = map.get_value(&system).0;
+    /// assert_eq!(map.modify_key(&mut system.modify(), "b"), None);
+    /// assert_eq!(map.get_value(&system).0, version);
+    /// *map.modify_key(&mut system.modify(), "a").unwrap() += 1;
+    /// assert_eq!(map.get_value(&system).1.get("a"), Some(&2));
+    /// assert_eq!(map.get_value(&system).0, system.version());
+    /// ```
+    pub fn modify_key<'s, Q: Eq + Hash + ?Sized>(
+        &'s self,
+        modify: &'s mut SystemModify,
+        key: &Q,
+    ) -> Option<&'s mut V>
+    where
+        K: Borrow<Q>,
+    {
+        if !self.contains_key(modify, key) {
+            return None;
+        }
+        let version = modify.version();
+        let state = self.state_mut(modify);
+        state.entries.entries.get_mut(key).map(|(v, value)| {
+            *v = version;
+            value
+        })
+    }
+
+    /// Whether `key` is present, without writing the map.
+    fn contains_key<Q: Eq + Hash + ?Sized>(&self, modify: &SystemModify, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.system_id.check_modify(modify);
+        unsafe { &*self.value.get() }.entries.contains_key(key)
+    }
+}
+
+impl<K, V> SystemNode for MapVar<K, V> {
+    type Value = MapEntries<K, V>;
+
+    fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    fn system_id(&self) -> SystemId {
+        self.system_id
+    }
+
+    fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
+        self.system_id.check_system(system);
+        let state = unsafe { &*self.value.get() };
+        (state.version, &state.entries)
+    }
+}
+
+impl<'s, T> Update<'s, T> {
+    /// Read one key, only depending on that key.
+    ///
+    /// A missing key depends on insertions and removals.
+    pub fn get_key<'r, K: Eq + Hash + Borrow<Q>, V, Q: Eq + Hash + ?Sized>(
+        &'r mut self,
+        node: &'r MapVar<K, V>,
+        key: &Q,
+    ) -> Option<&'r V> {
+        let state = node.state(self.system);
+        match state.entries.entries.get(key) {
+            Some((version, value)) => {
+                self.track_node(node.node_id(), *version);
+                Some(value)
+            }
+            None => {
+                self.track_node(node.node_id(), state.structure_version);
+                None
+            }
+        }
+    }
+
+    /// Read the keys of the map, only depending on insertions and removals.
+    pub fn get_map_keys<'r, K: Eq + Hash, V>(
+        &'r mut self,
+        node: &'r MapVar<K, V>,
+    ) -> impl Iterator<Item = &'r K> {
+        let state = node.state(self.system);
+        self.track_node(node.node_id(), state.structure_version);
+        state.entries.keys()
+    }
+}
@@ -0,0 +1,241 @@
+use std::cell::Cell;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Mutex;
+
+use crate::error::LazyCatchError;
+use crate::{System, SystemNode, SystemVersion};
+
+thread_local! {
+    static DISPATCHING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Marks an observer callback running on this thread, restoring the previous state on drop.
+struct Dispatch {
+    outer: bool,
+}
+
+impl Dispatch {
+    fn enter() -> Self {
+        Self {
+            outer: DISPATCHING.with(|dispatching| dispatching.replace(true)),
+        }
+    }
+}
+
+impl Drop for Dispatch {
+    fn drop(&mut self) {
+        DISPATCHING.with(|dispatching| dispatching.set(self.outer));
+    }
+}
+
+/// Whether an observer callback is running on this thread.
+pub(crate) fn is_dispatching() -> bool {
+    DISPATCHING.with(Cell::get)
+}
+
+/// Fail with [`LazyCatchError::ObserverUpdate`] if an observer callback is running on this thread.
+pub(crate) fn check_not_dispatching() {
+    if is_dispatching() {
+        LazyCatchError::ObserverUpdate.raise();
+    }
+}
+
+impl System {
+    /// Call `callback` with the new value of `node` each time [`Observer::poll`] sees a change.
+    ///
+    /// A callback may read nodes that are current and poll other observers, but a read that
+    /// would run an update fails with [`LazyCatchError::ObserverUpdate`], so the callbacks of
+    /// one poll all see the graph as it was when the poll started. Bring the nodes up to date
+    /// before polling, for example with [`System::warm_all`].
+    ///
+    /// ```
+    /// # use std::panic::{catch_unwind, AssertUnwindSafe};
+    /// # use lazy_catch::error::LazyCatchError;
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let double = system.val(|mut u: Update<i32>| {
+    ///     let x = *u.get(&x);
+    ///     u.update(|| x * 2);
+    /// });
+    /// let mut seen = Vec::new();
+    /// let mut observer = system.on_change(&x, |x: &i32| seen.push((*x, *system.get(&double))));
+    /// system.get(&double);
+    /// observer.poll(&system);
+    /// drop(observer);
+    /// assert_eq!(seen, [(1, 2)]);
+    ///
+    /// system.set(&x, 2);
+    /// let mut observer = system.on_change(&x, |_: &i32| {
+    ///     system.get(&double);
+    /// });
+    /// let payload = catch_unwind(AssertUnwindSafe(|| observer.poll(&system))).unwrap_err();
+    /// assert_eq!(
+    ///     payload.downcast_ref::<LazyCatchError>(),
+    ///     Some(&LazyCatchError::ObserverUpdate),
+    /// );
+    /// drop(observer);
+    /// assert_eq!(*system.get(&double), 4);
+    /// ```
+    #[must_use = "observers must be stored to be polled later"]
+    pub fn on_change<'n, N: SystemNode + ?Sized, C: FnMut(&N::Value)>(
+        &self,
+        node: &'n N,
+        callback: C,
+    ) -> Observer<'n, N, C> {
+        node.system_id().check_system(self);
+        Observer {
+            node,
+            seen: None,
+            callback,
+        }
+    }
+
+    /// Call `callback` with the old and new value of `node` each time [`ChangeObserver::poll`] sees a change.
+    ///
+    /// The observer keeps a clone of the last value it saw, the first change has no old value.
+    ///
+    /// ```
+    /// # use lazy_catch::System;
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let mut log = Vec::new();
+    /// let mut observer = system.on_change_with_old(&x, |old: Option<&i32>, new: &i32| {
+    ///     log.push((old.copied(), *new));
+    /// });
+    /// observer.poll(&system);
+    /// *x.modify(&mut system.modify()) = 2;
+    /// observer.poll(&system);
+    /// observer.poll(&system);
+    /// drop(observer);
+    /// assert_eq!(log, [(None, 1), (Some(1), 2)]);
+    /// ```
+    #[must_use = "observers must be stored to be polled later"]
+    pub fn on_change_with_old<'n, N: SystemNode + ?Sized, C: FnMut(Option<&N::Value>, &N::Value)>(
+        &self,
+        node: &'n N,
+        callback: C,
+    ) -> ChangeObserver<'n, N, C>
+    where
+        N::Value: Clone,
+    {
+        node.system_id().check_system(self);
+        ChangeObserver {
+            node,
+            seen: None,
+            old: None,
+            callback,
+        }
+    }
+
+    /// Call `callback` with the new version each time a [`SystemModify`] that wrote a node ends,
+    /// after the eager nodes are brought up to date.
+    ///
+    /// A modify that writes nothing, like [`Var::set_if_changed`](crate::var::Var::set_if_changed)
+    /// with an equal value, calls no callback. Callbacks are called in the order they were added.
+    ///
+    /// ```
+    /// # use std::sync::{Arc, Mutex};
+    /// # use lazy_catch::System;
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let frames = Arc::new(Mutex::new(Vec::new()));
+    /// system.on_commit({
+    ///     let frames = frames.clone();
+    ///     move |version| frames.lock().unwrap().push(("layout", version))
+    /// });
+    /// system.on_commit({
+    ///     let frames = frames.clone();
+    ///     move |version| frames.lock().unwrap().push(("paint", version))
+    /// });
+    /// system.modify();
+    /// x.set_if_changed(&mut system.modify(), 1);
+    /// assert!(frames.lock().unwrap().is_empty());
+    /// system.set(&x, 2);
+    /// let version = system.version();
+    /// assert_eq!(*frames.lock().unwrap(), [("layout", version), ("paint", version)]);
+    /// ```
+    ///
+    /// [`SystemModify`]: crate::SystemModify
+    pub fn on_commit(&mut self, callback: impl FnMut(SystemVersion) + Send + 'static) {
+        self.commit_callbacks.push(Box::new(callback));
+    }
+}
+
+pub struct Observer<'n, N: ?Sized, C> {
+    node: &'n N,
+    seen: Option<SystemVersion>,
+    callback: C,
+}
+
+impl<'n, N: SystemNode + ?Sized, C: FnMut(&N::Value)> Observer<'n, N, C> {
+    /// Read the node and call the callback if its value changed, returning whether it was called.
+    pub fn poll(&mut self, system: &System) -> bool {
+        let (version, value) = self.node.get_value(system);
+        if self.seen >= Some(version) {
+            return false;
+        }
+        self.seen = Some(version);
+        let _dispatch = Dispatch::enter();
+        (self.callback)(value);
+        true
+    }
+}
+
+pub struct ChangeObserver<'n, N: SystemNode + ?Sized, C>
+where
+    N::Value: Sized,
+{
+    node: &'n N,
+    seen: Option<SystemVersion>,
+    old: Option<N::Value>,
+    callback: C,
+}
+
+impl<'n, N: SystemNode + ?Sized, C: FnMut(Option<&N::Value>, &N::Value)> ChangeObserver<'n, N, C>
+where
+    N::Value: Clone,
+{
+    /// Read the node and call the callback if its value changed, returning whether it was called.
+    pub fn poll(&mut self, system: &System) -> bool {
+        let (version, value) = self.node.get_value(system);
+        if self.seen >= Some(version) {
+            return false;
+        }
+        self.seen = Some(version);
+        let dispatch = Dispatch::enter();
+        (self.callback)(self.old.as_ref(), value);
+        drop(dispatch);
+        self.old = Some(value.clone());
+        true
+    }
+}
+
+type CommitCallback = Box<dyn FnMut(SystemVersion) + Send>;
+
+/// The callbacks of [`System::on_commit`], in the order they were added.
+#[derive(Default)]
+pub(crate) struct CommitCallbacks {
+    callbacks: Mutex<Vec<CommitCallback>>,
+}
+
+impl CommitCallbacks {
+    fn push(&mut self, callback: CommitCallback) {
+        self.callbacks.get_mut().unwrap().push(callback);
+    }
+
+    pub(crate) fn call(&mut self, version: SystemVersion) {
+        for callback in self.callbacks.get_mut().unwrap() {
+            callback(version);
+        }
+    }
+}
+
+impl Debug for CommitCallbacks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let len = self.callbacks.lock().unwrap().len();
+        f.debug_struct("CommitCallbacks")
+            .field("len", &len)
+            .finish()
+    }
+}
@@ -0,0 +1,83 @@
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+
+use crate::val::Val;
+use crate::{System, SystemNode, Update};
+
+macro_rules! binary_ops {
+    ($($(#[$attr:meta])* $name:ident $op:ident;)*) => {
+        $(
+            $(#[$attr])*
+            #[must_use = "nodes must be stored to be read later"]
+            fn $name<B: SystemNode>(
+                self,
+                system: &System,
+                other: B,
+            ) -> Val<<Self::Value as $op<B::Value>>::Output, impl FnMut(Update<<Self::Value as $op<B::Value>>::Output>) + use<Self, B>>
+            where
+                Self::Value: Clone + $op<B::Value>,
+                B::Value: Clone + Sized,
+            {
+                system.val(move |mut u: Update<_>| {
+                    let a = u.get(&self);
+                    let b = u.get(&other);
+                    u.update(|| $op::$name(a.clone(), b.clone()));
+                })
+            }
+        )*
+    };
+}
+
+/// Arithmetic on the values of nodes, cloned only when the result is computed again.
+///
+/// The operands are moved into the new node: pass `&node` to borrow it,
+/// or an `Arc`/`Rc` of it for a node that owns its inputs.
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use lazy_catch::ops::NumNode;
+/// # use lazy_catch::System;
+/// let mut system = System::new();
+/// let price = system.var(3);
+/// let qty = system.var(2);
+/// let total = (&price).mul(&system, &qty);
+/// assert_eq!(*system.get(&total), 6);
+/// *qty.modify(&mut system.modify()) = 5;
+/// assert_eq!(*system.get(&total), 15);
+///
+/// let a = Arc::new(system.var(1));
+/// let b = Arc::new(system.var(2));
+/// let owned = a.clone().sub(&system, b.clone()).neg(&system);
+/// assert_eq!(*system.get(&owned), 1);
+/// ```
+pub trait NumNode: SystemNode + Sized
+where
+    Self::Value: Sized,
+{
+    binary_ops! {
+        add Add;
+        sub Sub;
+        mul Mul;
+        div Div;
+        rem Rem;
+    }
+
+    #[allow(clippy::type_complexity)]
+    #[must_use = "nodes must be stored to be read later"]
+    fn neg(
+        self,
+        system: &System,
+    ) -> Val<
+        <Self::Value as Neg>::Output,
+        impl FnMut(Update<<Self::Value as Neg>::Output>) + use<Self>,
+    >
+    where
+        Self::Value: Clone + Neg,
+    {
+        system.val(move |mut u: Update<_>| {
+            let a = u.get(&self);
+            u.update(|| -a.clone());
+        })
+    }
+}
+
+impl<N: SystemNode> NumNode for N where N::Value: Sized {}
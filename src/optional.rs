@@ -0,0 +1,128 @@
+use std::cell::UnsafeCell;
+
+use crate::error::LazyCatchError;
+use crate::{NodeId, System, SystemId, SystemNode, SystemVersion};
+
+impl System {
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn optional_node<'n, N: SystemNode + ?Sized>(
+        &self,
+        node: Option<&'n N>,
+    ) -> OptionalNode<'n, N, N::Value>
+    where
+        N::Value: Clone,
+    {
+        OptionalNode::new(self, node)
+    }
+}
+
+/// A node that may be absent, read as `Option` of a clone of its value.
+///
+/// Without a node the value is `None` at the creation version and never advances.
+/// With a node the version is forwarded, and the value is cloned once per version of the node.
+///
+/// ```
+/// # use lazy_catch::var::Var;
+/// # use lazy_catch::{System, SystemNode, Update};
+/// let mut system = System::new();
+/// let x = system.var(1);
+/// let some = system.optional_node(Some(&x));
+/// let none = system.optional_node::<Var<i32>>(None);
+/// let sum = system.val(|mut u: Update<i32>| {
+///     let total = [&some, &none].iter().map(|n| u.get(*n).unwrap_or(0)).sum();
+///     u.update(|| total);
+/// });
+/// assert_eq!(*system.get(&sum), 1);
+/// assert_eq!(system.get_versioned(&none), (none.created_version(), &None));
+/// system.set(&x, 5);
+/// assert_eq!(system.get_versioned(&some), (x.get_value(&system).0, &Some(5)));
+/// assert_eq!(*system.get(&sum), 5);
+/// ```
+pub struct OptionalNode<'n, N: ?Sized, T> {
+    system_id: SystemId,
+    node_id: NodeId,
+    created_version: SystemVersion,
+    node: Option<&'n N>,
+    /// The system version of the last read, with the version and clone it found.
+    value: UnsafeCell<(Option<SystemVersion>, SystemVersion, Option<T>)>,
+}
+
+impl<'n, N: SystemNode<Value = T> + ?Sized, T: Clone> OptionalNode<'n, N, T> {
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn new(system: &System, node: Option<&'n N>) -> Self {
+        if let Some(node) = node {
+            node.system_id().check_system(system);
+        }
+        Self {
+            system_id: system.id(),
+            node_id: NodeId::new(),
+            created_version: system.version(),
+            node,
+            value: UnsafeCell::new((None, system.version(), None)),
+        }
+    }
+
+    /// The system version when this node was created.
+    pub fn created_version(&self) -> SystemVersion {
+        self.created_version
+    }
+}
+
+impl<N: SystemNode<Value = T> + ?Sized, T: Clone> SystemNode for OptionalNode<'_, N, T> {
+    type Value = Option<T>;
+
+    fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    fn system_id(&self) -> SystemId {
+        self.system_id
+    }
+
+    fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
+        match self.try_get_value(system) {
+            Some(value) => value,
+            None => LazyCatchError::Aborted.raise(),
+        }
+    }
+
+    fn try_get_value<'s>(&'s self, system: &'s System) -> Option<(SystemVersion, &'s Self::Value)> {
+        self.system_id.check_system(system);
+        if let Some(node) = self.node {
+            let (checked, version, _) = unsafe { &*self.value.get() };
+            if *checked != Some(system.version()) {
+                let Some((node_version, value)) = node.try_get_value(system) else {
+                    if checked.is_none() {
+                        return None;
+                    }
+                    // Keep the last clone until the next version.
+                    unsafe { &mut *self.value.get() }.0 = Some(system.version());
+                    let (_, version, value) = unsafe { &*self.value.get() };
+                    return Some((*version, value));
+                };
+                // Values handed out at an older version can not be alive, a modify borrowed the system.
+                let cached = unsafe { &mut *self.value.get() };
+                if checked.is_none() || *version != node_version {
+                    *cached = (Some(system.version()), node_version, Some(value.clone()));
+                } else {
+                    cached.0 = Some(system.version());
+                }
+            }
+        }
+        let (_, version, value) = unsafe { &*self.value.get() };
+        Some((*version, value))
+    }
+
+    fn is_computed(&self) -> bool {
+        self.node.is_none_or(|node| node.is_computed())
+    }
+
+    fn is_current(&self, system: &System) -> bool {
+        match self.node {
+            Some(node) => {
+                unsafe { &*self.value.get() }.0 == Some(system.version()) && node.is_current(system)
+            }
+            None => true,
+        }
+    }
+}
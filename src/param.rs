@@ -0,0 +1,243 @@
+use std::cell::{Cell, UnsafeCell};
+
+use crate::error::LazyCatchError;
+use crate::observer;
+use crate::trace::NodeTrace;
+use crate::val::run_update;
+use crate::{NodeId, System, SystemId, SystemNode, SystemVersion, Update};
+
+impl System {
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn param_val<T, P: PartialEq, F: FnMut(&P, Update<T>)>(&self, f: F) -> ParamNode<T, P, F> {
+        ParamNode::new(self, f)
+    }
+
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let a = system.param_val(|p: &i32, mut u: Update<i32>| {
+    ///     let v = *u.get(&x);
+    ///     u.update(|| v * *p);
+    /// });
+    /// let two = system.get_with(&a, 2);
+    /// let three = system.get_with(&a, 3);
+    /// assert_eq!((*two, *three), (2, 3));
+    /// *x.modify(&mut system.modify()) = 10;
+    /// assert_eq!(*system.get_with(&a, 3), 30);
+    /// ```
+    ///
+    /// A node reading the param node sees a change when the last given parameter changes.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let a = system.param_val(|p: &i32, mut u: Update<i32>| {
+    ///     let v = *u.get(&x);
+    ///     u.update(|| v * *p);
+    /// });
+    /// let b = system.val(|mut u: Update<i32>| {
+    ///     let a = *u.get(&a);
+    ///     u.update(|| a);
+    /// });
+    /// system.get_with(&a, 2);
+    /// assert_eq!(*system.get(&b), 2);
+    /// system.modify();
+    /// system.get_with(&a, 3);
+    /// assert_eq!(*system.get(&b), 3);
+    /// ```
+    pub fn get_with<'s, T, P: PartialEq, F: FnMut(&P, Update<T>)>(
+        &'s self,
+        node: &'s ParamNode<T, P, F>,
+        param: P,
+    ) -> &'s T {
+        match node.get_param_value(self, Some(param)) {
+            Some((_, value)) => value,
+            None => LazyCatchError::Aborted.raise(),
+        }
+    }
+}
+
+/// The value for one parameter, boxed so that it stays in place while other parameters are
+/// added.
+struct ParamSlot<T, P> {
+    param: P,
+    check_version: Option<SystemVersion>,
+    value: Option<(SystemVersion, T)>,
+    /// The version this slot replaced another one as the slot of the last given parameter,
+    /// so that the node reports a change to the nodes reading it.
+    selected: Option<SystemVersion>,
+}
+
+struct ParamState<T, P, F> {
+    update_fn: F,
+    slots: Vec<Box<ParamSlot<T, P>>>,
+    /// The slot of the last given parameter.
+    last: Option<usize>,
+    /// The version the other slots were last dropped at.
+    pruned: Option<SystemVersion>,
+}
+
+/// A node whose closure also receives a non-reactive parameter given at read time.
+///
+/// Reading it as a [`SystemNode`] reuses the last given parameter. Each parameter read at the
+/// current version keeps its own value until the next modify.
+pub struct ParamNode<T, P, F = fn(&P, Update<T>)> {
+    system_id: SystemId,
+    node_id: NodeId,
+    created_version: SystemVersion,
+    lock: Cell<bool>,
+    trace: NodeTrace,
+    state: UnsafeCell<ParamState<T, P, F>>,
+}
+
+impl<T, P: PartialEq, F: FnMut(&P, Update<T>)> ParamNode<T, P, F> {
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn new(system: &System, f: F) -> Self {
+        Self {
+            system_id: system.id(),
+            node_id: NodeId::new(),
+            created_version: system.version(),
+            lock: Cell::new(false),
+            trace: NodeTrace::default(),
+            state: UnsafeCell::new(ParamState {
+                update_fn: f,
+                slots: Vec::new(),
+                last: None,
+                pruned: None,
+            }),
+        }
+    }
+
+    /// The system version when this node was created.
+    pub fn created_version(&self) -> SystemVersion {
+        self.created_version
+    }
+
+    /// The dependency with the newest version when the value was last stored,
+    /// `None` if it read no dependency.
+    #[cfg(feature = "trace")]
+    pub fn last_trigger(&self) -> Option<NodeId> {
+        self.trace.last_trigger()
+    }
+
+    fn get_param_value<'s>(
+        &'s self,
+        system: &'s System,
+        param: Option<P>,
+    ) -> Option<(SystemVersion, &'s T)> {
+        self.system_id.check_system(system);
+        system.record_get();
+        if self.lock.get() {
+            LazyCatchError::Recursion.raise();
+        }
+        let version = system.version();
+        let state = unsafe { &mut *self.state.get() };
+        if state.pruned != Some(version) {
+            // Values handed out at an older version can not be alive, a modify borrowed the system.
+            if let Some(last) = state.last.take() {
+                let slot = state.slots.swap_remove(last);
+                state.slots.clear();
+                state.slots.push(slot);
+                state.last = Some(0);
+            }
+            state.pruned = Some(version);
+        }
+        let index = match param {
+            Some(param) => match state.slots.iter().position(|slot| slot.param == param) {
+                Some(index) => index,
+                None => {
+                    state.slots.push(Box::new(ParamSlot {
+                        param,
+                        check_version: None,
+                        value: None,
+                        selected: None,
+                    }));
+                    state.slots.len() - 1
+                }
+            },
+            None => state
+                .last
+                .unwrap_or_else(|| LazyCatchError::MissingParam.raise()),
+        };
+        let slot = std::ptr::addr_of_mut!(*state.slots[index]);
+        if state.last.is_some_and(|last| last != index) {
+            unsafe { (*slot).selected = Some(version) };
+        }
+        state.last = Some(index);
+        let (check_version, computed) = unsafe { ((*slot).check_version, (*slot).value.is_some()) };
+        let stale = check_version != Some(version) && !(system.is_frozen() && computed);
+        if stale {
+            observer::check_not_dispatching();
+            self.lock.set(true);
+            // Only this slot is written, the values of the others may be borrowed.
+            let slot = unsafe { &mut *slot };
+            let update_fn = &mut state.update_fn;
+            let checked = run_update(
+                system,
+                self.node_id,
+                &mut slot.value,
+                false,
+                &self.trace,
+                |u| update_fn(&slot.param, u),
+            );
+            if checked {
+                slot.check_version = Some(version);
+            }
+            self.lock.set(false);
+        }
+        let slot = unsafe { &*slot };
+        let (version, value) = slot.value.as_ref()?;
+        Some((
+            slot.selected
+                .map_or(*version, |selected| selected.max(*version)),
+            value,
+        ))
+    }
+
+    fn last_slot(&self) -> Option<&ParamSlot<T, P>> {
+        let state = unsafe { &*self.state.get() };
+        state.last.map(|last| &*state.slots[last])
+    }
+}
+
+impl<T, P: PartialEq, F: FnMut(&P, Update<T>)> SystemNode for ParamNode<T, P, F> {
+    type Value = T;
+
+    fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    fn system_id(&self) -> SystemId {
+        self.system_id
+    }
+
+    fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
+        match self.get_param_value(system, None) {
+            Some(value) => value,
+            None => LazyCatchError::Aborted.raise(),
+        }
+    }
+
+    fn try_get_value<'s>(&'s self, system: &'s System) -> Option<(SystemVersion, &'s Self::Value)> {
+        self.get_param_value(system, None)
+    }
+
+    fn is_computed(&self) -> bool {
+        !self.lock.get() && self.last_slot().is_some_and(|slot| slot.value.is_some())
+    }
+
+    fn is_current(&self, system: &System) -> bool {
+        !self.lock.get()
+            && self
+                .last_slot()
+                .is_some_and(|slot| slot.check_version == Some(system.version()))
+    }
+
+    #[cfg(feature = "trace")]
+    fn last_deps(&self) -> Vec<(NodeId, SystemVersion)> {
+        self.trace.deps()
+    }
+}
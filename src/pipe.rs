@@ -0,0 +1,164 @@
+use std::marker::PhantomData;
+
+use crate::val::{BoxedVal, Val};
+use crate::{System, SystemNode, Update};
+
+impl System {
+    /// Start a chain of transformations of `node`, built into a single val.
+    ///
+    /// The value of `node` is cloned once per change and passed through the stages in order.
+    /// A stage that drops the value, like [`Pipe::filter`], keeps the last value that passed,
+    /// so the built node holds `None` until a value passes every stage.
+    ///
+    /// ```
+    /// # use lazy_catch::System;
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let even_squares = system
+    ///     .pipe(&x)
+    ///     .map(|x| x * x)
+    ///     .filter(|x| x % 2 == 0)
+    ///     .dedup()
+    ///     .build();
+    /// assert_eq!(*system.get(&even_squares), None);
+    /// system.set(&x, 2);
+    /// assert_eq!(*system.get(&even_squares), Some(4));
+    /// let version = system.get_versioned(&even_squares).0;
+    /// system.set(&x, 3);
+    /// system.set(&x, -2);
+    /// assert_eq!(system.get_versioned(&even_squares), (version, &Some(4)));
+    /// system.set(&x, 4);
+    /// assert_eq!(*system.get(&even_squares), Some(16));
+    /// ```
+    ///
+    /// Each stage nests the type of the chain, [`Pipe::boxed`] erases it at a small cost per run.
+    ///
+    /// ```
+    /// # use lazy_catch::System;
+    /// # use lazy_catch::val::BoxedVal;
+    /// let system = System::new();
+    /// let name = system.var(String::from(" lazy "));
+    /// let label: BoxedVal<Option<String>> = system
+    ///     .pipe(&name)
+    ///     .map(|name| name.trim().to_owned())
+    ///     .boxed()
+    ///     .map(|name| name.to_uppercase())
+    ///     .build_boxed();
+    /// assert_eq!(system.get(&label).as_deref(), Some("LAZY"));
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn pipe<'s, 'n, N: SystemNode + ?Sized>(
+        &'s self,
+        node: &'n N,
+    ) -> Pipe<'s, 'n, N, N::Value, impl FnMut(&N::Value) -> Option<N::Value> + 'n>
+    where
+        N::Value: Clone,
+    {
+        Pipe {
+            system: self,
+            node,
+            stage: |value: &N::Value| Some(value.clone()),
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// See [`System::pipe`].
+pub struct Pipe<'s, 'n, N: ?Sized, T, P> {
+    system: &'s System,
+    node: &'n N,
+    stage: P,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'s, 'n, N, T, P> Pipe<'s, 'n, N, T, P>
+where
+    N: SystemNode + ?Sized,
+    T: 'n,
+    P: FnMut(&N::Value) -> Option<T> + 'n,
+{
+    /// Transform each value.
+    pub fn map<U: 'n, F: FnMut(T) -> U + 'n>(
+        self,
+        mut f: F,
+    ) -> Pipe<'s, 'n, N, U, impl FnMut(&N::Value) -> Option<U> + 'n> {
+        let mut stage = self.stage;
+        Pipe {
+            system: self.system,
+            node: self.node,
+            stage: move |value: &N::Value| stage(value).map(&mut f),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Drop the values `f` rejects.
+    pub fn filter<F: FnMut(&T) -> bool + 'n>(
+        self,
+        mut f: F,
+    ) -> Pipe<'s, 'n, N, T, impl FnMut(&N::Value) -> Option<T> + 'n> {
+        let mut stage = self.stage;
+        Pipe {
+            system: self.system,
+            node: self.node,
+            stage: move |value: &N::Value| stage(value).filter(&mut f),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Drop the values equal to the last one that passed this stage.
+    pub fn dedup(self) -> Pipe<'s, 'n, N, T, impl FnMut(&N::Value) -> Option<T> + 'n>
+    where
+        T: Clone + PartialEq,
+    {
+        let mut last = None;
+        self.filter(move |value: &T| {
+            if last.as_ref() == Some(value) {
+                return false;
+            }
+            last = Some(value.clone());
+            true
+        })
+    }
+
+    /// Erase the type of the stages so far behind a box.
+    #[allow(clippy::type_complexity)]
+    pub fn boxed(self) -> Pipe<'s, 'n, N, T, Box<dyn FnMut(&N::Value) -> Option<T> + 'n>> {
+        Pipe {
+            system: self.system,
+            node: self.node,
+            stage: Box::new(self.stage),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Build the chain into a val.
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn build(self) -> Val<Option<T>, impl FnMut(Update<Option<T>>) + 'n> {
+        let system = self.system;
+        system.val(self.into_update())
+    }
+
+    /// Build the chain into a boxed val, with a type that does not depend on the stages.
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn build_boxed(self) -> BoxedVal<'n, Option<T>> {
+        let system = self.system;
+        system.boxed_val(self.into_update())
+    }
+
+    fn into_update(self) -> impl FnMut(Update<Option<T>>) + 'n {
+        let node = self.node;
+        let mut stage = self.stage;
+        move |mut u: Update<Option<T>>| {
+            let (version, value) = node.get_value(u.system());
+            u.track_node(node.node_id(), version);
+            if u.current_version >= Some(version) {
+                return u.keep();
+            }
+            match stage(value) {
+                Some(value) => u.update(|| Some(value)),
+                None if u.receiver.is_some() => u.keep(),
+                None => u.update(|| None),
+            }
+        }
+    }
+}
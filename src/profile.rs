@@ -0,0 +1,91 @@
+#[cfg(feature = "profile")]
+use std::cell::RefCell;
+#[cfg(feature = "profile")]
+use std::time::Instant;
+
+use crate::NodeId;
+#[cfg(feature = "profile")]
+use crate::System;
+
+/// One run of an update closure.
+#[cfg(feature = "profile")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ProfileEntry {
+    pub node_id: NodeId,
+    pub enter: Instant,
+    pub exit: Instant,
+}
+
+#[cfg(feature = "profile")]
+thread_local! {
+    static PROFILE: RefCell<Vec<ProfileEntry>> = const { RefCell::new(Vec::new()) };
+}
+
+#[cfg(feature = "profile")]
+impl System {
+    /// Take the runs of update closures on this thread since the last call, of any system.
+    ///
+    /// Runs are listed as they finish, so a dependency updated by a node comes before it.
+    /// Sort by `enter` for the order they started in.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, SystemNode, Update};
+    /// let system = System::new();
+    /// let x = system.var(1);
+    /// let a = system.val(|mut u: Update<i32>| {
+    ///     let x = *u.get(&x);
+    ///     u.update(|| x + 1);
+    /// });
+    /// let b = system.val(|mut u: Update<i32>| {
+    ///     let a = *u.get(&a);
+    ///     u.update(|| a * 2);
+    /// });
+    /// System::take_profile();
+    /// system.get(&b);
+    /// let profile = System::take_profile();
+    /// let ids: Vec<_> = profile.iter().map(|entry| entry.node_id).collect();
+    /// assert_eq!(ids, [a.node_id(), b.node_id()]);
+    /// assert!(profile[1].enter <= profile[0].enter);
+    /// ```
+    pub fn take_profile() -> Vec<ProfileEntry> {
+        PROFILE.with(|profile| std::mem::take(&mut *profile.borrow_mut()))
+    }
+}
+
+/// A running update closure, recorded when dropped with the `profile` feature.
+pub(crate) struct ProfileSpan {
+    #[cfg(feature = "profile")]
+    node_id: NodeId,
+    #[cfg(feature = "profile")]
+    enter: Instant,
+}
+
+impl ProfileSpan {
+    #[inline]
+    pub(crate) fn enter(node_id: NodeId) -> Self {
+        #[cfg(not(feature = "profile"))]
+        let _ = node_id;
+        Self {
+            #[cfg(feature = "profile")]
+            node_id,
+            #[cfg(feature = "profile")]
+            enter: Instant::now(),
+        }
+    }
+
+    /// End the span, the record is made by its drop.
+    #[inline]
+    pub(crate) fn exit(self) {}
+}
+
+#[cfg(feature = "profile")]
+impl Drop for ProfileSpan {
+    fn drop(&mut self) {
+        let entry = ProfileEntry {
+            node_id: self.node_id,
+            enter: self.enter,
+            exit: Instant::now(),
+        };
+        PROFILE.with(|profile| profile.borrow_mut().push(entry));
+    }
+}
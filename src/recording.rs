@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Mutex, MutexGuard};
+
+use crate::{NodeId, System};
+
+/// A system counting the runs of the update closure of each node, to assert how lazy a graph
+/// is in tests.
+///
+/// A run is counted whether or not it stored a new value, the same as
+/// [`SystemStats::runs`](crate::stats::SystemStats::runs).
+///
+/// ```
+/// # use lazy_catch::recording::RecordingSystem;
+/// # use lazy_catch::{SystemNode, Update};
+/// let mut system = RecordingSystem::new();
+/// let x = system.var(1);
+/// let y = system.var(1);
+/// let a = system.val(|mut u: Update<i32>| {
+///     let x = *u.get(&x);
+///     u.update(|| x + 1);
+/// });
+/// let b = system.val(|mut u: Update<i32>| {
+///     let a = *u.get(&a);
+///     u.update(|| a * 2);
+/// });
+/// system.set(&y, 2);
+/// system.assert_recomputes(a.node_id(), 0);
+/// system.get(&b);
+/// system.get(&b);
+/// system.assert_recomputes(a.node_id(), 1);
+/// system.assert_recomputes(b.node_id(), 1);
+///
+/// system.reset_recomputes();
+/// system.set(&y, 3);
+/// system.get(&b);
+/// system.assert_recomputes(a.node_id(), 1);
+/// system.assert_recomputes(b.node_id(), 1);
+/// system.set(&x, 2);
+/// system.get(&b);
+/// assert_eq!(system.recomputes(), [(a.node_id(), 2), (b.node_id(), 2)]);
+/// ```
+#[derive(Debug)]
+pub struct RecordingSystem {
+    system: System,
+}
+
+impl RecordingSystem {
+    pub fn new() -> Self {
+        let mut system = System::new();
+        system.recording = Some(Mutex::new(BTreeMap::new()));
+        Self { system }
+    }
+
+    /// The runs of `node` since creation or the last [`RecordingSystem::reset_recomputes`].
+    pub fn recompute_count(&self, node: NodeId) -> u64 {
+        self.runs().get(&node).copied().unwrap_or(0)
+    }
+
+    /// The nodes that ran and their runs, ordered by node id.
+    pub fn recomputes(&self) -> Vec<(NodeId, u64)> {
+        self.runs()
+            .iter()
+            .map(|(&node, &runs)| (node, runs))
+            .collect()
+    }
+
+    /// Panic unless `node` ran exactly `expected` times.
+    #[track_caller]
+    pub fn assert_recomputes(&self, node: NodeId, expected: u64) {
+        let runs = self.recompute_count(node);
+        assert_eq!(
+            runs, expected,
+            "node {node:?} recomputed {runs} times, expected {expected}"
+        );
+    }
+
+    /// Start counting again from zero.
+    pub fn reset_recomputes(&mut self) {
+        self.system
+            .recording
+            .as_mut()
+            .unwrap()
+            .get_mut()
+            .unwrap()
+            .clear();
+    }
+
+    fn runs(&self) -> MutexGuard<'_, BTreeMap<NodeId, u64>> {
+        self.system.recording.as_ref().unwrap().lock().unwrap()
+    }
+}
+
+impl Default for RecordingSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for RecordingSystem {
+    type Target = System;
+
+    fn deref(&self) -> &Self::Target {
+        &self.system
+    }
+}
+
+impl DerefMut for RecordingSystem {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.system
+    }
+}
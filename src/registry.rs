@@ -0,0 +1,399 @@
+use std::any::TypeId;
+#[cfg(feature = "trace")]
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+#[cfg(feature = "trace")]
+use crate::trace::NodeTrace;
+use crate::val::AtomicOptionVersion;
+use crate::{NodeId, System, Tag};
+
+/// State of a registered node shared with its [`System`].
+#[derive(Debug)]
+pub(crate) struct NodeRecord {
+    pub(crate) check_version: AtomicOptionVersion,
+    tag: Option<Tag>,
+    /// Set by [`System::optimize`] and [`SyncVal::seal`](crate::val::SyncVal::seal).
+    sealed: AtomicBool,
+    /// The nodes read by the last update, see [`System::to_dot`].
+    #[cfg(feature = "trace")]
+    deps: Mutex<Vec<NodeId>>,
+    /// Whether the last update depended on the system version, such a node is never sealed.
+    #[cfg(feature = "trace")]
+    on_version: AtomicBool,
+    /// The value type and the last value formatted with `Debug`, see [`System::debug_dump`].
+    debug: Option<(TypeId, Mutex<Option<String>>)>,
+}
+
+impl NodeRecord {
+    pub(crate) fn seal(&self) {
+        self.sealed.store(true, Ordering::Release);
+    }
+
+    pub(crate) fn is_sealed(&self) -> bool {
+        self.sealed.load(Ordering::Acquire)
+    }
+
+    #[cfg(feature = "trace")]
+    pub(crate) fn set_deps(&self, trace: &NodeTrace) {
+        *self.deps.lock().unwrap() = trace
+            .deps()
+            .into_iter()
+            .map(|(node_id, _)| node_id)
+            .collect();
+        self.on_version.store(trace.on_version(), Ordering::Release);
+    }
+
+    pub(crate) fn set_debug(&self, value: String) {
+        if let Some((_, debug)) = &self.debug {
+            *debug.lock().unwrap() = Some(value);
+        }
+    }
+}
+
+impl System {
+    pub(crate) fn register(&self, node_id: NodeId, tag: Option<Tag>) -> Arc<NodeRecord> {
+        self.insert_record(node_id, tag, None)
+    }
+
+    pub(crate) fn register_debug(
+        &self,
+        node_id: NodeId,
+        tag: Option<Tag>,
+        value_type: TypeId,
+    ) -> Arc<NodeRecord> {
+        self.insert_record(node_id, tag, Some((value_type, Mutex::new(None))))
+    }
+
+    fn insert_record(
+        &self,
+        node_id: NodeId,
+        tag: Option<Tag>,
+        debug: Option<(TypeId, Mutex<Option<String>>)>,
+    ) -> Arc<NodeRecord> {
+        let record = Arc::new(NodeRecord {
+            check_version: AtomicOptionVersion::new(),
+            tag,
+            sealed: AtomicBool::new(false),
+            #[cfg(feature = "trace")]
+            deps: Mutex::new(Vec::new()),
+            #[cfg(feature = "trace")]
+            on_version: AtomicBool::new(false),
+            debug,
+        });
+        let mut registry = self.registry.lock().unwrap();
+        registry.retain(|_, record| record.strong_count() > 0);
+        registry.insert(node_id, Arc::downgrade(&record));
+        record
+    }
+
+    /// Whether every registered node has been checked at the current version.
+    ///
+    /// Nodes that are not registered are ignored, and sealed nodes count as checked.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let mut a = system.val(|mut u: Update<i32>| {
+    ///     let v = *u.get(&x);
+    ///     u.update(|| v + 1);
+    /// });
+    /// a.register(&system);
+    /// assert!(!system.is_settled());
+    /// system.get(&a);
+    /// assert!(system.is_settled());
+    /// *x.modify(&mut system.modify()) = 2;
+    /// assert!(!system.is_settled());
+    /// ```
+    pub fn is_settled(&self) -> bool {
+        let version = Some(self.version());
+        let registry = self.registry.lock().unwrap();
+        registry
+            .values()
+            .filter_map(|record| record.upgrade())
+            .all(|record| record.is_sealed() || record.check_version.get() == version)
+    }
+
+    /// The last computed values of the nodes registered with `register_debug` whose value
+    /// type is `T`, formatted with `Debug` and ordered by node id.
+    ///
+    /// Nodes are not read, a node that never computed is left out.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, SystemNode, Update};
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let mut a = system.val(|mut u: Update<i32>| {
+    ///     let x = *u.get(&x);
+    ///     u.update(|| x + 1);
+    /// });
+    /// let mut b = system.val(|mut u: Update<i32>| {
+    ///     let x = *u.get(&x);
+    ///     u.update(|| x * 10);
+    /// });
+    /// a.register_debug(&system);
+    /// b.register_debug(&system);
+    /// system.get(&a);
+    /// assert_eq!(system.debug_dump::<i32>(), [(a.node_id(), String::from("2"))]);
+    /// system.set(&x, 2);
+    /// system.get(&a);
+    /// system.get(&b);
+    /// let dump = system.debug_dump::<i32>();
+    /// assert_eq!(dump, [(a.node_id(), String::from("3")), (b.node_id(), String::from("20"))]);
+    /// assert!(system.debug_dump::<String>().is_empty());
+    /// ```
+    pub fn debug_dump<T: 'static>(&self) -> Vec<(NodeId, String)> {
+        let value_type = TypeId::of::<T>();
+        let registry = self.registry.lock().unwrap();
+        registry
+            .iter()
+            .filter_map(|(&node_id, record)| {
+                let record = record.upgrade()?;
+                let (record_type, debug) = record.debug.as_ref()?;
+                if *record_type != value_type {
+                    return None;
+                }
+                let value = debug.lock().unwrap().clone()?;
+                Some((node_id, value))
+            })
+            .collect()
+    }
+
+    /// The registered nodes carrying `tag`, ordered by node id.
+    pub fn tagged_nodes(&self, tag: Tag) -> Vec<NodeId> {
+        let registry = self.registry.lock().unwrap();
+        registry
+            .iter()
+            .filter(|(_, record)| {
+                record
+                    .upgrade()
+                    .is_some_and(|record| record.tag == Some(tag))
+            })
+            .map(|(&node_id, _)| node_id)
+            .collect()
+    }
+
+    /// The registered nodes and the nodes each of them read at its last update,
+    /// as a Graphviz DOT graph with an edge from each node to its dependencies.
+    ///
+    /// Dependencies can change from one update to the next, the graph only shows the last ones.
+    /// Nodes are labeled with their id, their tag and their value if registered with
+    /// `register_debug`. Dependencies that are not registered, like vars, have no label,
+    /// and nodes that never computed have no edges.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// let system = System::new();
+    /// let x = system.var(1);
+    /// let mut a = system.val(|mut u: Update<i32>| {
+    ///     let x = *u.get(&x);
+    ///     u.update(|| x + 1);
+    /// });
+    /// a.register_debug(&system);
+    /// let mut b = system.val(|mut u: Update<i32>| {
+    ///     let a = *u.get(&a);
+    ///     u.update(|| a * 2);
+    /// });
+    /// b.register(&system);
+    /// assert!(!system.to_dot().contains("->"));
+    /// system.get(&b);
+    /// let dot = system.to_dot();
+    /// assert!(dot.starts_with("digraph {"));
+    /// assert_eq!(dot.matches("->").count(), 2);
+    /// assert!(dot.contains(": 2\""));
+    /// ```
+    #[cfg(feature = "trace")]
+    pub fn to_dot(&self) -> String {
+        use std::fmt::Write;
+
+        let registry = self.registry.lock().unwrap();
+        let mut dot = String::from("digraph {\n");
+        for (node_id, record) in registry.iter() {
+            let Some(record) = record.upgrade() else {
+                continue;
+            };
+            let mut label = node_id.id.to_string();
+            if let Some(Tag(tag)) = record.tag {
+                write!(label, " tag {tag}").unwrap();
+            }
+            if let Some((_, debug)) = &record.debug {
+                if let Some(value) = &*debug.lock().unwrap() {
+                    write!(label, ": {value}").unwrap();
+                }
+            }
+            writeln!(dot, "    n{} [label={label:?}];", node_id.id).unwrap();
+            for dep in record.deps.lock().unwrap().iter() {
+                writeln!(dot, "    n{} -> n{};", node_id.id, dep.id).unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// The registered nodes that depend on `node`, directly or through other registered nodes,
+    /// ordered by node id.
+    ///
+    /// Edges are only known once a node ran its update, so this follows the dependencies each
+    /// node read at its last update, see [`System::to_dot`].
+    ///
+    /// ```
+    /// # use lazy_catch::{System, SystemNode, Update};
+    /// let system = System::new();
+    /// let x = system.var(1);
+    /// let y = system.var(1);
+    /// let mut a = system.val(|mut u: Update<i32>| {
+    ///     let x = *u.get(&x);
+    ///     u.update(|| x + 1);
+    /// });
+    /// a.register(&system);
+    /// let mut b = system.val(|mut u: Update<i32>| {
+    ///     let a = *u.get(&a);
+    ///     u.update(|| a * 2);
+    /// });
+    /// b.register(&system);
+    /// let mut c = system.val(|mut u: Update<i32>| {
+    ///     let y = *u.get(&y);
+    ///     u.update(|| y);
+    /// });
+    /// c.register(&system);
+    /// assert!(system.dependents_of(x.node_id()).is_empty());
+    /// system.get(&b);
+    /// system.get(&c);
+    /// assert_eq!(system.dependents_of(x.node_id()), [a.node_id(), b.node_id()]);
+    /// assert_eq!(system.dependents_of(y.node_id()), [c.node_id()]);
+    /// ```
+    #[cfg(feature = "trace")]
+    pub fn dependents_of(&self, node: NodeId) -> Vec<NodeId> {
+        let registry = self.registry.lock().unwrap();
+        let mut dependents = BTreeMap::<NodeId, Vec<NodeId>>::new();
+        for (&node_id, record) in registry.iter() {
+            if let Some(record) = record.upgrade() {
+                for &dep in record.deps.lock().unwrap().iter() {
+                    dependents.entry(dep).or_default().push(node_id);
+                }
+            }
+        }
+        let mut found = BTreeSet::new();
+        let mut pending = vec![node];
+        while let Some(node_id) = pending.pop() {
+            for &dependent in dependents.get(&node_id).into_iter().flatten() {
+                if found.insert(dependent) {
+                    pending.push(dependent);
+                }
+            }
+        }
+        found.remove(&node);
+        found.into_iter().collect()
+    }
+
+    /// Declare that `node` is never written again, as an input of [`System::optimize`].
+    ///
+    /// A later write to it through a [`SystemModify`](crate::SystemModify) fails with
+    /// [`LazyCatchError::ConstantWrite`](crate::error::LazyCatchError::ConstantWrite).
+    #[cfg(feature = "trace")]
+    pub fn mark_constant(&mut self, node: NodeId) {
+        self.constants.insert(node);
+    }
+
+    /// Seal the registered nodes whose dependencies are all constant, so later reads skip
+    /// the version check.
+    ///
+    /// A node is sealed when it is current, and each node its last update read was marked with
+    /// [`System::mark_constant`] or is a sealed registered node, so sealing spreads through
+    /// chains of registered nodes. A node that read nothing counts as constant too, but not one
+    /// that called [`Update::depend_on_version`](crate::Update::depend_on_version). Nodes that
+    /// are stale are left alone, bring them up to date first.
+    ///
+    /// This is only sound while the constant inputs are never written again, which the system
+    /// checks at each write, and while update closures read nothing outside the graph.
+    ///
+    /// ```
+    /// # use std::panic::{catch_unwind, AssertUnwindSafe};
+    /// # use lazy_catch::error::LazyCatchError;
+    /// # use lazy_catch::{System, SystemNode, Update};
+    /// let mut system = System::new();
+    /// let size = system.var(8);
+    /// let scale = system.var(2);
+    /// let mut buffer = system.val(|mut u: Update<Vec<u8>>| {
+    ///     let size = *u.get(&size);
+    ///     u.update(|| vec![0; size]);
+    /// });
+    /// buffer.register(&system);
+    /// let mut len = system.sync_val(|mut u: Update<usize>| {
+    ///     let len = u.get(&buffer).len();
+    ///     u.update(|| len);
+    /// });
+    /// len.register(&system);
+    /// let mut scaled = system.sync_val(|mut u: Update<usize>| {
+    ///     let len = *u.get(&len) * *u.get(&scale);
+    ///     u.update(|| len);
+    /// });
+    /// scaled.register(&system);
+    /// system.get(&scaled);
+    ///
+    /// system.mark_constant(size.node_id());
+    /// system.optimize();
+    /// assert!(len.is_sealed());
+    /// assert!(!scaled.is_sealed());
+    /// system.set(&scale, 3);
+    /// assert_eq!(*system.get(&scaled), 24);
+    ///
+    /// let payload = catch_unwind(AssertUnwindSafe(|| system.set(&size, 4))).unwrap_err();
+    /// assert_eq!(
+    ///     payload.downcast_ref::<LazyCatchError>(),
+    ///     Some(&LazyCatchError::ConstantWrite),
+    /// );
+    /// ```
+    ///
+    /// A node depending on the system version stays unsealed.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let mut frames = system.val(|mut u: Update<u64>| {
+    ///     u.depend_on_version();
+    ///     u.update_with_old(|old| old.map_or(0, |frame| frame + 1));
+    /// });
+    /// frames.register(&system);
+    /// assert_eq!(*system.get(&frames), 0);
+    /// system.optimize();
+    /// assert!(!frames.is_sealed());
+    /// system.modify();
+    /// assert_eq!(*system.get(&frames), 1);
+    /// ```
+    #[cfg(feature = "trace")]
+    pub fn optimize(&mut self) {
+        let version = Some(self.version());
+        let registry = self.registry.lock().unwrap();
+        let records: BTreeMap<NodeId, Arc<NodeRecord>> = registry
+            .iter()
+            .filter_map(|(&node_id, record)| Some((node_id, record.upgrade()?)))
+            .collect();
+        let is_constant = |node_id: &NodeId| {
+            self.constants.contains(node_id)
+                || records
+                    .get(node_id)
+                    .is_some_and(|record| record.is_sealed())
+        };
+        loop {
+            let mut sealed = false;
+            for record in records.values() {
+                if record.is_sealed()
+                    || record.check_version.get() != version
+                    || record.on_version.load(Ordering::Acquire)
+                {
+                    continue;
+                }
+                if record.deps.lock().unwrap().iter().all(is_constant) {
+                    record.seal();
+                    sealed = true;
+                }
+            }
+            if !sealed {
+                break;
+            }
+        }
+    }
+}
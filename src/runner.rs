@@ -0,0 +1,92 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+
+use crate::eager::EagerNode;
+use crate::val::SyncVal;
+use crate::{System, SystemModify, Update};
+
+/// A [`System`] driven by messages, each applied in its own modify.
+///
+/// The eager nodes it registers are kept alive by the runner, so they are brought up to date
+/// after every message.
+///
+/// ```
+/// # use std::sync::mpsc::channel;
+/// # use std::sync::Arc;
+/// # use lazy_catch::runner::SystemRunner;
+/// # use lazy_catch::Update;
+/// let mut runner = SystemRunner::new();
+/// let x = Arc::new(runner.var(0));
+/// let doubled = runner.register_eager({
+///     let x = x.clone();
+///     move |mut u: Update<i32>| {
+///         let x = *u.get(&*x);
+///         u.update(|| x * 2);
+///     }
+/// });
+/// let (tx, rx) = channel();
+/// std::thread::spawn(move || {
+///     for n in 1..=3 {
+///         tx.send(n).unwrap();
+///     }
+/// });
+/// runner.run(rx, |modify, n| *x.modify(modify) += n);
+/// assert_eq!(*runner.get(&*doubled), 12);
+/// ```
+#[derive(Default)]
+pub struct SystemRunner {
+    system: System,
+    eager: Vec<Arc<dyn EagerNode>>,
+}
+
+impl SystemRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an eager node of the system that lives as long as the runner.
+    pub fn register_eager<T, F>(&mut self, f: F) -> Arc<SyncVal<T, F>>
+    where
+        T: Send + Sync + 'static,
+        F: FnMut(Update<T>) + Send + 'static,
+    {
+        let node = self.system.eager_val(f);
+        self.eager.push(node.clone());
+        node
+    }
+
+    /// Apply each message received from `rx` in a new modify, until every sender is gone.
+    pub fn run<M>(&mut self, rx: Receiver<M>, mut apply: impl FnMut(&mut SystemModify, M)) {
+        for message in rx {
+            apply(&mut self.system.modify(), message);
+        }
+    }
+
+    pub fn into_inner(self) -> System {
+        self.system
+    }
+}
+
+impl From<System> for SystemRunner {
+    fn from(system: System) -> Self {
+        Self {
+            system,
+            eager: Vec::new(),
+        }
+    }
+}
+
+impl Deref for SystemRunner {
+    type Target = System;
+
+    fn deref(&self) -> &System {
+        &self.system
+    }
+}
+
+impl DerefMut for SystemRunner {
+    fn deref_mut(&mut self) -> &mut System {
+        &mut self.system
+    }
+}
@@ -0,0 +1,267 @@
+use std::cell::{Cell, UnsafeCell};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use crate::val::Val;
+use crate::{NodeId, System, SystemId, SystemNode, SystemVersion, Update};
+
+impl System {
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn async_stale_val<I, T, C, F>(
+        &self,
+        initial: T,
+        capture: C,
+        compute: F,
+    ) -> AsyncStaleVal<I, T, C, F>
+    where
+        I: Clone + Send + 'static,
+        T: Send + 'static,
+        C: FnMut(Update<I>),
+        F: Fn(I) -> T + Send + Sync + 'static,
+    {
+        AsyncStaleVal::new(self, initial, capture, compute)
+    }
+}
+
+type Pending<T> = Arc<Mutex<Option<(SystemVersion, T)>>>;
+
+/// A node computed on a background thread, serving the last finished value while it refreshes.
+///
+/// An [`Update`] borrows the system and can not leave the reading thread, so the work is split:
+/// `capture` reads the dependencies into an input `I` on the reading thread,
+/// and `compute` turns a clone of that input into the value on a spawned thread.
+/// The input is taken at one version, so a refresh never mixes dependency versions.
+///
+/// Reads never block. A finished refresh is served from the first read at a later system version,
+/// stamped with that version, so references from earlier reads stay valid
+/// and downstream nodes see the change like any other.
+///
+/// ```
+/// # use lazy_catch::{System, Update};
+/// let mut system = System::new();
+/// let x = system.var(1);
+/// let a = system.async_stale_val(
+///     0,
+///     |mut u: Update<i32>| {
+///         let v = *u.get(&x);
+///         u.update(|| v);
+///     },
+///     |v: i32| v * 10,
+/// );
+/// assert_eq!(*system.get(&a), 0);
+/// a.join();
+/// system.modify();
+/// assert_eq!(*system.get(&a), 10);
+///
+/// *x.modify(&mut system.modify()) = 2;
+/// assert_eq!(*system.get(&a), 10);
+/// a.join();
+/// system.modify();
+/// assert_eq!(*system.get(&a), 20);
+/// ```
+pub struct AsyncStaleVal<I, T, C, F> {
+    system_id: SystemId,
+    node_id: NodeId,
+    input: Val<I, C>,
+    compute: Arc<F>,
+    check_version: Cell<Option<SystemVersion>>,
+    spawned: Cell<Option<SystemVersion>>,
+    served: Cell<Option<SystemVersion>>,
+    pending: Pending<T>,
+    worker: Cell<Option<JoinHandle<()>>>,
+    value: UnsafeCell<(SystemVersion, T)>,
+}
+
+impl<I, T, C, F> AsyncStaleVal<I, T, C, F>
+where
+    I: Clone + Send + 'static,
+    T: Send + 'static,
+    C: FnMut(Update<I>),
+    F: Fn(I) -> T + Send + Sync + 'static,
+{
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn new(system: &System, initial: T, capture: C, compute: F) -> Self {
+        Self {
+            system_id: system.id(),
+            node_id: NodeId::new(),
+            input: Val::new(system, capture),
+            compute: Arc::new(compute),
+            check_version: Cell::new(None),
+            spawned: Cell::new(None),
+            served: Cell::new(None),
+            pending: Arc::new(Mutex::new(None)),
+            worker: Cell::new(None),
+            value: UnsafeCell::new((system.version(), initial)),
+        }
+    }
+
+    /// Whether a refresh has been spawned that is not served yet.
+    pub fn is_pending(&self) -> bool {
+        self.spawned.get() > self.served.get()
+    }
+
+    /// Wait for the last spawned refresh to finish.
+    pub fn join(&self) {
+        if let Some(worker) = self.worker.take() {
+            worker.join().unwrap();
+        }
+    }
+
+    fn spawn(&self, input_version: SystemVersion, input: I) {
+        self.spawned.set(Some(input_version));
+        let compute = self.compute.clone();
+        let pending = self.pending.clone();
+        let worker = std::thread::spawn(move || {
+            let value = compute(input);
+            let mut pending = pending.lock().unwrap();
+            if pending.as_ref().map(|&(v, _)| v) < Some(input_version) {
+                *pending = Some((input_version, value));
+            }
+        });
+        self.worker.set(Some(worker));
+    }
+}
+
+impl<I, T, C, F> SystemNode for AsyncStaleVal<I, T, C, F>
+where
+    I: Clone + Send + 'static,
+    T: Send + 'static,
+    C: FnMut(Update<I>),
+    F: Fn(I) -> T + Send + Sync + 'static,
+{
+    type Value = T;
+
+    fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    fn system_id(&self) -> SystemId {
+        self.system_id
+    }
+
+    fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
+        self.system_id.check_system(system);
+        if self.check_version.get() != Some(system.version()) {
+            if let Some((input_version, value)) = self.pending.lock().unwrap().take() {
+                if self.served.get() < Some(input_version) {
+                    self.served.set(Some(input_version));
+                    *unsafe { &mut *self.value.get() } = (system.version(), value);
+                }
+            }
+            // A capture that aborted before storing a value spawns nothing.
+            if let Some((input_version, input)) = self.input.try_get_value(system) {
+                if self.spawned.get() < Some(input_version) {
+                    self.spawn(input_version, input.clone());
+                }
+            }
+            self.check_version.set(Some(system.version()));
+        }
+        let (version, value) = unsafe { &*self.value.get() };
+        (*version, value)
+    }
+
+    fn is_current(&self, system: &System) -> bool {
+        self.check_version.get() == Some(system.version())
+    }
+}
+
+impl System {
+    /// A node whose value is whether `target` is stale, without running the update of `target`.
+    ///
+    /// See [`StaleNode`].
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn is_stale_node<'n, N: SystemNode + ?Sized>(&self, target: &'n N) -> StaleNode<'n, N> {
+        StaleNode::new(self, target)
+    }
+}
+
+/// Whether a target node was not checked at the current version, as a node.
+///
+/// The value is taken from [`SystemNode::is_current`] at the first read in each system version
+/// and kept for the rest of that version, so references to it stay valid.
+/// Read it before the target to show a loading indicator while the target is behind.
+/// The version only advances when the value flips, so nodes reading it rerun only then.
+///
+/// ```
+/// # use lazy_catch::{System, SystemNode, Update};
+/// let mut system = System::new();
+/// let x = system.var(1);
+/// let heavy = system.val(|mut u: Update<i32>| {
+///     let x = *u.get(&x);
+///     u.update(|| x * 100);
+/// });
+/// let loading = system.is_stale_node(&heavy);
+/// assert!(*system.get(&loading));
+/// assert!(!heavy.is_computed());
+/// assert_eq!(*system.get(&heavy), 100);
+///
+/// system.modify();
+/// system.get(&heavy);
+/// assert!(!*system.get(&loading));
+///
+/// *x.modify(&mut system.modify()) = 2;
+/// assert!(*system.get(&loading));
+/// ```
+pub struct StaleNode<'n, N: ?Sized> {
+    system_id: SystemId,
+    node_id: NodeId,
+    created_version: SystemVersion,
+    target: &'n N,
+    value: UnsafeCell<Option<(SystemVersion, SystemVersion, bool)>>,
+}
+
+impl<'n, N: SystemNode + ?Sized> StaleNode<'n, N> {
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn new(system: &System, target: &'n N) -> Self {
+        target.system_id().check_system(system);
+        Self {
+            system_id: system.id(),
+            node_id: NodeId::new(),
+            created_version: system.version(),
+            target,
+            value: UnsafeCell::new(None),
+        }
+    }
+
+    /// The system version when this node was created.
+    pub fn created_version(&self) -> SystemVersion {
+        self.created_version
+    }
+}
+
+impl<N: SystemNode + ?Sized> SystemNode for StaleNode<'_, N> {
+    type Value = bool;
+
+    fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    fn system_id(&self) -> SystemId {
+        self.system_id
+    }
+
+    fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
+        self.system_id.check_system(system);
+        let checked = unsafe { &*self.value.get() }.map(|(checked, _, _)| checked);
+        if checked != Some(system.version()) {
+            // Values handed out at an older version can not be alive, a modify borrowed the system.
+            let stale = !self.target.is_current(system);
+            let value = unsafe { &mut *self.value.get() };
+            let changed = match *value {
+                Some((_, changed, old)) if old == stale => changed,
+                _ => system.version(),
+            };
+            *value = Some((system.version(), changed, stale));
+        }
+        let (_, version, value) = unsafe { (*self.value.get()).as_ref().unwrap() };
+        (*version, value)
+    }
+
+    fn is_computed(&self) -> bool {
+        unsafe { &*self.value.get() }.is_some()
+    }
+
+    fn is_current(&self, system: &System) -> bool {
+        unsafe { &*self.value.get() }.map(|(checked, _, _)| checked) == Some(system.version())
+    }
+}
@@ -0,0 +1,83 @@
+#[cfg(feature = "stats")]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{NodeId, System};
+
+/// Counters of how lazy the nodes of a system are.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct SystemStats {
+    /// Reads of nodes with an update closure.
+    pub gets: u64,
+    /// Runs of update closures.
+    pub runs: u64,
+    /// Runs that kept the old value because no dependency advanced.
+    pub skipped: u64,
+}
+
+#[cfg(feature = "stats")]
+#[derive(Debug, Default)]
+pub(crate) struct StatsCounter {
+    gets: AtomicU64,
+    runs: AtomicU64,
+    skipped: AtomicU64,
+}
+
+impl System {
+    /// The counters since creation, all zero without the `stats` feature.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let y = system.var(1);
+    /// let a = system.val(|mut u: Update<i32>| {
+    ///     let v = *u.get(&x);
+    ///     u.update(|| v);
+    /// });
+    /// system.get(&a);
+    /// system.get(&a);
+    /// *y.modify(&mut system.modify()) = 2;
+    /// system.get(&a);
+    /// let stats = system.stats();
+    /// assert_eq!((stats.gets, stats.runs, stats.skipped), (3, 2, 1));
+    /// ```
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> SystemStats {
+        SystemStats {
+            gets: self.stats.gets.load(Ordering::Relaxed),
+            runs: self.stats.runs.load(Ordering::Relaxed),
+            skipped: self.stats.skipped.load(Ordering::Relaxed),
+        }
+    }
+
+    /// The counters since creation, all zero without the `stats` feature.
+    #[cfg(not(feature = "stats"))]
+    pub fn stats(&self) -> SystemStats {
+        SystemStats::default()
+    }
+
+    #[inline]
+    pub(crate) fn record_get(&self) {
+        #[cfg(feature = "stats")]
+        self.stats.gets.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn record_run(&self, node_id: NodeId, stored: bool) {
+        #[cfg(feature = "testing")]
+        if let Some(runs) = &self.recording {
+            *runs.lock().unwrap().entry(node_id).or_default() += 1;
+        }
+        #[cfg(not(feature = "testing"))]
+        let _ = node_id;
+        #[cfg(feature = "stats")]
+        {
+            self.stats.runs.fetch_add(1, Ordering::Relaxed);
+            if !stored {
+                self.stats.skipped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        #[cfg(not(feature = "stats"))]
+        let _ = stored;
+    }
+}
@@ -0,0 +1,68 @@
+#[cfg(feature = "trace")]
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+#[cfg(feature = "trace")]
+use std::sync::{Mutex, PoisonError};
+
+#[cfg(feature = "trace")]
+use crate::{NodeId, SystemVersion};
+
+/// Debug state of a node, empty without the `trace` feature.
+#[derive(Debug, Default)]
+pub(crate) struct NodeTrace {
+    #[cfg(feature = "trace")]
+    last_trigger: AtomicU64,
+    /// The nodes read by the last run, in the order of their first read.
+    #[cfg(feature = "trace")]
+    deps: Mutex<Vec<(NodeId, SystemVersion)>>,
+    /// Whether the last run called [`Update::depend_on_version`](crate::Update::depend_on_version).
+    #[cfg(feature = "trace")]
+    on_version: AtomicBool,
+}
+
+#[cfg(feature = "trace")]
+impl NodeTrace {
+    pub(crate) fn set(&self, trigger: Option<NodeId>) {
+        let id = trigger.map_or(0, |node_id| node_id.id + 1);
+        self.last_trigger.store(id, Ordering::Relaxed);
+    }
+
+    pub(crate) fn last_trigger(&self) -> Option<NodeId> {
+        match self.last_trigger.load(Ordering::Relaxed) {
+            0 => None,
+            id => Some(NodeId { id: id - 1 }),
+        }
+    }
+
+    pub(crate) fn clear_deps(&self) {
+        self.on_version.store(false, Ordering::Relaxed);
+        self.deps
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    pub(crate) fn push_dep(&self, node_id: NodeId, version: SystemVersion) {
+        let mut deps = self.deps.lock().unwrap_or_else(PoisonError::into_inner);
+        if deps.iter().all(|&(id, _)| id != node_id) {
+            deps.push((node_id, version));
+        }
+    }
+
+    pub(crate) fn set_on_version(&self) {
+        self.on_version.store(true, Ordering::Relaxed);
+    }
+
+    pub(crate) fn on_version(&self) -> bool {
+        self.on_version.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn deps(&self) -> Vec<(NodeId, SystemVersion)> {
+        let mut deps = self
+            .deps
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        deps.sort();
+        deps
+    }
+}
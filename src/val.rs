@@ -1,118 +1,1023 @@
-use std::cell::{Cell, UnsafeCell};
+use std::any::TypeId;
+use std::cell::{Cell, RefCell, UnsafeCell};
+use std::fmt::Debug;
 use std::num::NonZeroU64;
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Mutex;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+use std::thread::ThreadId;
 
-use crate::{System, SystemId, SystemNode, SystemVersion, Update};
+use crate::access::NodeAccess;
+use crate::arena::ValueSlot;
+use crate::error::LazyCatchError;
+use crate::observer;
+use crate::profile::ProfileSpan;
+use crate::registry::NodeRecord;
+use crate::trace::NodeTrace;
+use crate::{NodeId, System, SystemId, SystemNode, SystemVersion, Tag, Update};
 
 impl System {
+    #[must_use = "nodes must be stored to be read later"]
     pub fn val<T, F: FnMut(Update<T>)>(&self, f: F) -> Val<T, F> {
         Val::new(self, f)
     }
 }
 
-pub struct Val<T, F = fn(Update<T>)> {
+impl System {
+    /// A val carrying `tag`, added to the registry so [`System::tagged_nodes`] finds it.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, SystemNode, Tag, Update};
+    /// const LAYOUT: Tag = Tag(1);
+    /// const STYLE: Tag = Tag(2);
+    /// let system = System::new();
+    /// let width = system.tagged_val(LAYOUT, |u: Update<i32>| u.update(|| 100));
+    /// let height = system.tagged_val(LAYOUT, |u: Update<i32>| u.update(|| 50));
+    /// let color = system.tagged_val(STYLE, |u: Update<&str>| u.update(|| "red"));
+    /// let plain = system.val(|u: Update<i32>| u.update(|| 0));
+    /// assert_eq!(width.tag(), Some(LAYOUT));
+    /// assert_eq!(plain.tag(), None);
+    /// assert_eq!(system.tagged_nodes(LAYOUT), [width.node_id(), height.node_id()]);
+    /// assert_eq!(system.tagged_nodes(STYLE), [color.node_id()]);
+    /// ```
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn tagged_val<T, F: FnMut(Update<T>)>(&self, tag: Tag, f: F) -> Val<T, F> {
+        let mut val = Val::new(self, f);
+        val.tag = Some(tag);
+        val.register(self);
+        val
+    }
+}
+
+pub type BoxedVal<'f, T> = Val<T, Box<dyn FnMut(Update<T>) + 'f>>;
+
+impl System {
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// let system = System::new();
+    /// let x = system.var(1);
+    /// let mut a = system.boxed_val(|mut u: Update<i32>| {
+    ///     let v = *u.get(&x);
+    ///     u.update(|| v + 1);
+    /// });
+    /// assert_eq!(*system.get(&a), 2);
+    ///
+    /// a.set_closure(Box::new(|mut u: Update<i32>| {
+    ///     let v = *u.get(&x);
+    ///     u.update(|| v * 10);
+    /// }));
+    /// assert_eq!(*system.get(&a), 10);
+    /// ```
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn boxed_val<'f, T, F: FnMut(Update<T>) + 'f>(&self, f: F) -> BoxedVal<'f, T> {
+        Val::new(self, Box::new(f))
+    }
+}
+
+impl System {
+    /// A val that keeps its old value and version when `eq` finds the new one equal to it.
+    ///
+    /// `eq` is only called with a previous value. Only [`Update::update`] compares,
+    /// a value stored by `update_with_old` always counts as a change.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let x = system.var(1.0);
+    /// let rounded = system.val_eq_by(
+    ///     |a: &f64, b: &f64| (a - b).abs() < 0.1,
+    ///     |mut u: Update<f64>| {
+    ///         let x = *u.get(&x);
+    ///         u.update(|| x);
+    ///     },
+    /// );
+    /// let version = system.get_versioned(&rounded).0;
+    /// system.set(&x, 1.05);
+    /// assert_eq!(system.get_versioned(&rounded), (version, &1.0));
+    /// system.set(&x, 2.0);
+    /// assert_eq!(*system.get(&rounded), 2.0);
+    /// ```
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn val_eq_by<'f, T: 'f, E: Fn(&T, &T) -> bool + 'f, F: FnMut(Update<T>) + 'f>(
+        &self,
+        eq: E,
+        mut f: F,
+    ) -> Val<T, impl FnMut(Update<T>) + 'f> {
+        let computed = Cell::new(None);
+        self.val(move |u: Update<T>| {
+            // Shorten the update to the borrows of this call, so it can hold the comparator.
+            let mut u: Update<'_, T> = u;
+            u.current_version = u.current_version.max(computed.get());
+            u.eq = Some((&eq, &computed));
+            f(u);
+        })
+    }
+
+    /// A val that keeps its old value and version when the new one is equal to it.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let x = system.var(3);
+    /// let parity = system.val_eq(|mut u: Update<i32>| {
+    ///     let x = *u.get(&x);
+    ///     u.update(|| x % 2);
+    /// });
+    /// let version = system.get_versioned(&parity).0;
+    /// system.set(&x, 5);
+    /// assert_eq!(system.get_versioned(&parity), (version, &1));
+    /// ```
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn val_eq<'f, T: PartialEq + 'f, F: FnMut(Update<T>) + 'f>(
+        &self,
+        f: F,
+    ) -> Val<T, impl FnMut(Update<T>) + 'f> {
+        self.val_eq_by(T::eq, f)
+    }
+}
+
+pub struct Val<T, F = fn(Update<T>), S = Option<(SystemVersion, T)>> {
     system_id: SystemId,
+    node_id: NodeId,
+    created_version: SystemVersion,
     check_version: Cell<Option<SystemVersion>>,
     lock: Cell<bool>,
-    value: UnsafeCell<(F, Option<(SystemVersion, T)>)>,
+    record: Option<Arc<NodeRecord>>,
+    dump: Option<fn(&T) -> String>,
+    tag: Option<Tag>,
+    trace: NodeTrace,
+    access: NodeAccess,
+    value: UnsafeCell<(F, S)>,
 }
 
 impl<T, F: FnMut(Update<T>)> Val<T, F> {
+    #[must_use = "nodes must be stored to be read later"]
     pub fn new(system: &System, f: F) -> Self {
+        Self::with_slot(system, f, None)
+    }
+}
+
+impl<T, F: FnMut(Update<T>), S: ValueSlot<T>> Val<T, F, S> {
+    /// A val keeping its value in `slot`, see [`System::with_arena`].
+    pub(crate) fn with_slot(system: &System, f: F, slot: S) -> Self {
         Self {
             system_id: system.id(),
+            node_id: NodeId::new(),
+            created_version: system.version(),
             check_version: Cell::new(None),
             lock: Cell::new(false),
-            value: UnsafeCell::new((f, None)),
+            record: None,
+            dump: None,
+            tag: None,
+            trace: NodeTrace::default(),
+            access: NodeAccess::default(),
+            value: UnsafeCell::new((f, slot)),
         }
     }
-}
 
-impl<T, F: FnMut(Update<T>)> SystemNode for Val<T, F> {
-    type Value = T;
+    /// The system version when this node was created.
+    pub fn created_version(&self) -> SystemVersion {
+        self.created_version
+    }
 
-    fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
+    /// Add this node to the registry of `system`.
+    pub fn register(&mut self, system: &System) {
+        self.system_id.check_system(system);
+        self.record = Some(system.register(self.node_id, self.tag));
+    }
+
+    /// Add this node to the registry of `system`, keeping its value formatted for [`System::debug_dump`].
+    pub fn register_debug(&mut self, system: &System)
+    where
+        T: Debug + 'static,
+    {
         self.system_id.check_system(system);
-        if self.check_version.get() != Some(system.version()) {
+        self.record = Some(system.register_debug(self.node_id, self.tag, TypeId::of::<T>()));
+        self.dump = Some(|value| format!("{value:?}"));
+    }
+
+    /// The dependency with the newest version when the value was last stored,
+    /// `None` if it read no dependency.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, SystemNode, Update};
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let y = system.var(1);
+    /// let a = system.val(|mut u: Update<i32>| {
+    ///     let v = *u.get(&x) + *u.get(&y);
+    ///     u.update(|| v);
+    /// });
+    /// system.get(&a);
+    /// *y.modify(&mut system.modify()) = 2;
+    /// system.get(&a);
+    /// assert_eq!(a.last_trigger(), Some(y.node_id()));
+    /// ```
+    #[cfg(feature = "trace")]
+    pub fn last_trigger(&self) -> Option<NodeId> {
+        self.trace.last_trigger()
+    }
+
+    /// The system version of the last read of this node, `None` if it was never read.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let a = system.val(|u: Update<i32>| u.update(|| 1));
+    /// assert_eq!(a.last_read_version(), None);
+    /// system.get(&a);
+    /// let read = system.version();
+    /// system.modify();
+    /// assert_eq!(a.last_read_version(), Some(read));
+    /// ```
+    #[cfg(feature = "access")]
+    pub fn last_read_version(&self) -> Option<SystemVersion> {
+        self.access.last_read_version()
+    }
+
+    /// Move the node to the heap so its address stays the same for its lifetime.
+    ///
+    /// The node keeps no pointers to itself, pinning only guarantees the address,
+    /// so it can be handed to callback systems that store `&Val` as a raw pointer.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// let system = System::new();
+    /// let a = system.val(|u: Update<i32>| u.update(|| 1)).pin();
+    /// let address: *const _ = &*a;
+    /// assert_eq!(*system.get(&*a), 1);
+    /// assert_eq!(address, &*a as *const _);
+    /// ```
+    pub fn pin(self) -> Pin<Box<Self>> {
+        Box::pin(self)
+    }
+
+    /// Whether an update of this node is running, or was left unfinished by a panic.
+    #[cfg(feature = "testing")]
+    pub fn is_locked(&self) -> bool {
+        self.lock.get()
+    }
+
+    /// Release the recursion lock left behind by a panicking update.
+    ///
+    /// ```
+    /// # use std::panic::{catch_unwind, AssertUnwindSafe};
+    /// # use lazy_catch::{System, Update};
+    /// let system = System::new();
+    /// let a = system.val(|_: Update<i32>| panic!());
+    /// catch_unwind(AssertUnwindSafe(|| system.get(&a))).unwrap_err();
+    /// assert!(a.is_locked());
+    /// a.reset_lock();
+    /// assert!(!a.is_locked());
+    /// ```
+    #[cfg(feature = "testing")]
+    pub fn reset_lock(&self) {
+        self.lock.set(false);
+    }
+
+    /// Replace the update closure, the next read will run it and store its result at the current version.
+    pub fn set_closure(&mut self, f: F) {
+        self.value.get_mut().0 = f;
+        self.check_version.set(None);
+    }
+
+    /// Whether `System::optimize` of the `trace` feature sealed this node.
+    pub fn is_sealed(&self) -> bool {
+        self.record
+            .as_ref()
+            .is_some_and(|record| record.is_sealed())
+    }
+
+    /// Read like [`SystemNode::try_get_value`], with `on_replace` running on each value the
+    /// update lets go of.
+    pub(crate) fn try_get_value_with<'s>(
+        &'s self,
+        system: &'s System,
+        on_replace: Option<&dyn Fn(&T)>,
+    ) -> Option<(SystemVersion, &'s T)> {
+        self.system_id.check_system(system);
+        system.record_get();
+        self.access.record(system.version());
+        if self.check_version.get() != Some(system.version())
+            && !(system.is_frozen() && unsafe { &*self.value.get() }.1.value().is_some())
+            && !self.is_sealed()
+        {
             if self.lock.get() {
-                panic!("Val update recursion");
+                LazyCatchError::Recursion.raise();
             }
+            observer::check_not_dispatching();
             self.lock.set(true);
-            let (update_fn, value) = unsafe { &mut *self.value.get() };
-            update_fn(Update::new(system, value.as_ref().map(|&(v, _)| v), value));
-            self.check_version.set(Some(system.version()));
+            let (update_fn, slot) = unsafe { &mut *self.value.get() };
+            let value = slot.value_mut();
+            let replaced = self.check_version.get().is_none() && value.is_some();
+            let checked = run_update(system, self.node_id, value, replaced, &self.trace, |u| {
+                // Shorten the update to the borrows of this call, so it can hold the hook.
+                let mut u: Update<'_, T> = u;
+                u.on_replace = on_replace;
+                update_fn(u);
+            });
+            if checked {
+                self.check_version.set(Some(system.version()));
+                if let Some(record) = &self.record {
+                    record.check_version.set(Some(system.version()));
+                    #[cfg(feature = "trace")]
+                    record.set_deps(&self.trace);
+                    if let (Some(dump), Some((_, value))) = (self.dump, value) {
+                        record.set_debug(dump(value));
+                    }
+                }
+            }
             self.lock.set(false);
         }
-        let (version, value) = unsafe { &*self.value.get() }.1.as_ref().unwrap();
-        (*version, value)
+        let (version, value) = unsafe { &*self.value.get() }.1.value().as_ref()?;
+        Some((*version, value))
+    }
+
+    /// Mark the value as checked at the current version without running the closure.
+    pub(crate) fn mark_current(&self, system: &System) {
+        self.system_id.check_system(system);
+        self.check_version.set(Some(system.version()));
+        if let Some(record) = &self.record {
+            record.check_version.set(Some(system.version()));
+        }
+    }
+}
+
+impl<T, F, S: ValueSlot<T>> Val<T, F, S> {
+    /// The stored value, for the drop of a node built on this one.
+    pub(crate) fn stored_value(&mut self) -> Option<&T> {
+        self.value
+            .get_mut()
+            .1
+            .value()
+            .as_ref()
+            .map(|(_, value)| value)
+    }
+}
+
+impl<T, F: FnMut(Update<T>), S: ValueSlot<T>> SystemNode for Val<T, F, S> {
+    type Value = T;
+
+    fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    fn system_id(&self) -> SystemId {
+        self.system_id
+    }
+
+    fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
+        match self.try_get_value(system) {
+            Some(value) => value,
+            None => LazyCatchError::Aborted.raise(),
+        }
+    }
+
+    fn try_get_value<'s>(&'s self, system: &'s System) -> Option<(SystemVersion, &'s Self::Value)> {
+        self.try_get_value_with(system, None)
+    }
+
+    fn is_computed(&self) -> bool {
+        !self.lock.get() && unsafe { &*self.value.get() }.1.value().is_some()
+    }
+
+    fn is_current(&self, system: &System) -> bool {
+        self.is_sealed() || self.check_version.get() == Some(system.version())
+    }
+
+    #[cfg(feature = "trace")]
+    fn last_deps(&self) -> Vec<(NodeId, SystemVersion)> {
+        self.trace.deps()
+    }
+
+    fn tag(&self) -> Option<Tag> {
+        self.tag
     }
 }
 
-struct AtomicOptionVersion {
+/// Run the update closure of a node on its cached value.
+///
+/// With `force` the result is stored even if no dependency advanced, at the current version.
+/// Returns `false` if the closure called [`Update::abort`] without a previous value, the node
+/// must not be marked checked. An abort with a previous value counts as checked: running the
+/// update again at this version could replace a value handed out by an earlier read.
+pub(crate) fn run_update<T, F: FnOnce(Update<T>)>(
+    system: &System,
+    node_id: NodeId,
+    value: &mut Option<(SystemVersion, T)>,
+    force: bool,
+    trace: &NodeTrace,
+    f: F,
+) -> bool {
+    let before = value.as_ref().map(|&(v, _)| v);
+    let aborted = Cell::new(false);
+    let mut update = Update::new(system, before, value);
+    update.aborted = Some(&aborted);
+    #[cfg(feature = "trace")]
+    {
+        trace.clear_deps();
+        update.trace = Some((trace, None));
+    }
+    #[cfg(not(feature = "trace"))]
+    let _ = trace;
+    if force {
+        update.current_version = None;
+        update.update_version = Some(system.version());
+    }
+    let span = ProfileSpan::enter(node_id);
+    f(update);
+    span.exit();
+    debug_assert!(
+        aborted.get() || value.is_some(),
+        "Val closure returned without update"
+    );
+    system.record_run(node_id, value.as_ref().map(|&(v, _)| v) != before);
+    !aborted.get() || value.is_some()
+}
+
+#[derive(Debug)]
+pub(crate) struct AtomicOptionVersion {
     inner: AtomicU64,
 }
 
 impl AtomicOptionVersion {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
             inner: AtomicU64::new(0),
         }
     }
 
-    fn get(&self) -> Option<SystemVersion> {
+    pub(crate) fn get(&self) -> Option<SystemVersion> {
         NonZeroU64::new(self.inner.load(Ordering::Acquire)).map(|version| SystemVersion { version })
     }
 
-    fn set(&self, v: Option<SystemVersion>) {
+    pub(crate) fn set(&self, v: Option<SystemVersion>) {
         self.inner
             .store(v.map(|s| s.version.get()).unwrap_or(0), Ordering::Release);
     }
 }
 
 impl System {
+    #[must_use = "nodes must be stored to be read later"]
     pub fn sync_val<T, F: FnMut(Update<T>)>(&self, f: F) -> SyncVal<T, F> {
         SyncVal::new(self, f)
     }
+
+    /// Bring each of `nodes` up to date, without keeping any borrow of their values.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, SystemNode, Update};
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let a = system.sync_val(|mut u: Update<i32>| {
+    ///     let x = *u.get(&x);
+    ///     u.update(|| x + 1);
+    /// });
+    /// let b = system.sync_val(|mut u: Update<i32>| {
+    ///     let x = *u.get(&x);
+    ///     u.update(|| x * 2);
+    /// });
+    /// system.set(&x, 5);
+    /// system.warm_all(&[&a as &dyn SystemNode<Value = i32>, &b]);
+    /// assert_eq!(*system.get(&a), 6);
+    /// assert_eq!(*system.get(&b), 10);
+    /// ```
+    pub fn warm_all<V: ?Sized>(&self, nodes: &[&dyn SystemNode<Value = V>]) {
+        for node in nodes {
+            node.get_value(self);
+        }
+    }
 }
 
-pub struct SyncVal<T, F = fn(Update<T>)> {
+/// The lock guarding the update of a [`SyncVal`].
+///
+/// [`Mutex`] is used by default, [`SyncVal::with_lock`] takes any other implementation.
+/// The lock is only taken when the node is stale, reads of a current value do not touch it.
+/// A read finding the lock held by an update on another thread waits for it with
+/// [`ValLock::lock`], one finding it held by an update on its own thread fails with
+/// [`LazyCatchError::Recursion`].
+///
+/// ```
+/// # use std::sync::atomic::{AtomicBool, Ordering};
+/// # use lazy_catch::error::LazyCatchError;
+/// # use lazy_catch::val::{SyncVal, ValLock};
+/// # use lazy_catch::{System, Update};
+/// #[derive(Default)]
+/// struct SpinLock(AtomicBool);
+///
+/// struct SpinGuard<'a>(&'a AtomicBool);
+///
+/// impl Drop for SpinGuard<'_> {
+///     fn drop(&mut self) {
+///         self.0.store(false, Ordering::Release);
+///     }
+/// }
+///
+/// impl ValLock for SpinLock {
+///     type Guard<'a> = SpinGuard<'a>;
+///
+///     fn lock(&self) -> Result<SpinGuard<'_>, LazyCatchError> {
+///         loop {
+///             if let Ok(guard) = self.try_lock() {
+///                 return Ok(guard);
+///             }
+///             std::hint::spin_loop();
+///         }
+///     }
+///
+///     fn try_lock(&self) -> Result<SpinGuard<'_>, LazyCatchError> {
+///         self.0
+///             .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+///             .map(|_| SpinGuard(&self.0))
+///             .map_err(|_| LazyCatchError::Recursion)
+///     }
+/// }
+///
+/// let mut system = System::new();
+/// let x = system.var(1);
+/// let a = SyncVal::with_lock(
+///     &system,
+///     |mut u: Update<i32>| {
+///         let x = *u.get(&x);
+///         u.update(|| x * 2);
+///     },
+///     SpinLock::default(),
+/// );
+/// assert_eq!(*system.get(&a), 2);
+/// system.set(&x, 2);
+/// assert_eq!(*system.get(&a), 4);
+/// ```
+pub trait ValLock {
+    type Guard<'a>
+    where
+        Self: 'a;
+
+    /// Acquire the lock, waiting for the current holder.
+    fn lock(&self) -> Result<Self::Guard<'_>, LazyCatchError>;
+
+    /// Acquire the lock if it is free.
+    ///
+    /// Fails with [`LazyCatchError::Recursion`] if it is held,
+    /// and with [`LazyCatchError::Poisoned`] if a holder panicked.
+    fn try_lock(&self) -> Result<Self::Guard<'_>, LazyCatchError>;
+}
+
+impl ValLock for Mutex<()> {
+    type Guard<'a> = MutexGuard<'a, ()>;
+
+    fn lock(&self) -> Result<Self::Guard<'_>, LazyCatchError> {
+        Mutex::lock(self).map_err(|_| LazyCatchError::Poisoned)
+    }
+
+    fn try_lock(&self) -> Result<Self::Guard<'_>, LazyCatchError> {
+        match Mutex::try_lock(self) {
+            Ok(lock) => Ok(lock),
+            Err(TryLockError::WouldBlock) => Err(LazyCatchError::Recursion),
+            Err(TryLockError::Poisoned(_)) => Err(LazyCatchError::Poisoned),
+        }
+    }
+}
+
+thread_local! {
+    static UPDATING: RefCell<Vec<NodeId>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Marks the update of a [`SyncVal`] running on this thread, until dropped.
+struct Updating;
+
+impl Updating {
+    fn enter(node_id: NodeId) -> Self {
+        UPDATING.with(|updating| updating.borrow_mut().push(node_id));
+        Self
+    }
+}
+
+impl Drop for Updating {
+    fn drop(&mut self) {
+        UPDATING.with(|updating| updating.borrow_mut().pop());
+    }
+}
+
+/// Whether the update of the [`SyncVal`] `node_id` is running on this thread.
+fn is_updating(node_id: NodeId) -> bool {
+    UPDATING.with(|updating| updating.borrow().contains(&node_id))
+}
+
+pub struct SyncVal<T, F = fn(Update<T>), L = Mutex<()>> {
     system_id: SystemId,
+    node_id: NodeId,
+    created_version: SystemVersion,
     check_version: AtomicOptionVersion,
-    lock: Mutex<()>,
+    sealed: AtomicBool,
+    lock: L,
+    record: Option<Arc<NodeRecord>>,
+    dump: Option<fn(&T) -> String>,
+    trace: NodeTrace,
+    access: NodeAccess,
     value: UnsafeCell<(F, Option<(SystemVersion, T)>)>,
 }
 
 impl<T, F: FnMut(Update<T>)> SyncVal<T, F> {
+    #[must_use = "nodes must be stored to be read later"]
     pub fn new(system: &System, f: F) -> Self {
+        Self::with_lock(system, f, Mutex::new(()))
+    }
+
+    /// Whether an update of this node is running, or its lock was poisoned by a panic.
+    #[cfg(feature = "testing")]
+    pub fn is_locked(&self) -> bool {
+        self.lock.is_poisoned() || self.lock.try_lock().is_err()
+    }
+
+    /// Clear the poison left behind by a panicking update.
+    ///
+    /// ```
+    /// # use std::panic::{catch_unwind, AssertUnwindSafe};
+    /// # use lazy_catch::{System, Update};
+    /// let system = System::new();
+    /// let a = system.sync_val(|_: Update<i32>| panic!());
+    /// catch_unwind(AssertUnwindSafe(|| system.get(&a))).unwrap_err();
+    /// assert!(a.is_locked());
+    /// a.reset_lock();
+    /// assert!(!a.is_locked());
+    /// ```
+    #[cfg(feature = "testing")]
+    pub fn reset_lock(&self) {
+        self.lock.clear_poison();
+    }
+}
+
+impl<T, F: FnMut(Update<T>), L: ValLock> SyncVal<T, F, L> {
+    /// Create a node whose updates are guarded by `lock` instead of a [`Mutex`].
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn with_lock(system: &System, f: F, lock: L) -> Self {
         Self {
             system_id: system.id(),
+            node_id: NodeId::new(),
+            created_version: system.version(),
             check_version: AtomicOptionVersion::new(),
-            lock: Mutex::new(()),
+            sealed: AtomicBool::new(false),
+            lock,
+            record: None,
+            dump: None,
+            trace: NodeTrace::default(),
+            access: NodeAccess::default(),
             value: UnsafeCell::new((f, None)),
         }
     }
+
+    /// The system version when this node was created.
+    pub fn created_version(&self) -> SystemVersion {
+        self.created_version
+    }
+
+    /// Add this node to the registry of `system`.
+    pub fn register(&mut self, system: &System) {
+        self.system_id.check_system(system);
+        self.record = Some(system.register(self.node_id, None));
+    }
+
+    /// Add this node to the registry of `system`, keeping its value formatted for [`System::debug_dump`].
+    pub fn register_debug(&mut self, system: &System)
+    where
+        T: Debug + 'static,
+    {
+        self.system_id.check_system(system);
+        self.record = Some(system.register_debug(self.node_id, None, TypeId::of::<T>()));
+        self.dump = Some(|value| format!("{value:?}"));
+    }
+
+    /// Recompute the value if it is stale, so later reads from other threads find it cached.
+    pub fn warm(&self, system: &System) {
+        self.get_value(system);
+    }
+
+    /// Bring the value up to date and keep it for good, so later reads skip the lock and the
+    /// version check.
+    ///
+    /// Sealing can not be undone. Only seal a node whose dependencies never change again:
+    /// a sealed node keeps serving its value when they do. With the `trace` feature, debug
+    /// builds panic when a node read by the last update is written.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let config = system.var(8);
+    /// let other = system.var(0);
+    /// let buffer = system.sync_val(|mut u: Update<Vec<u8>>| {
+    ///     let size = *u.get(&config);
+    ///     u.update(|| vec![0; size]);
+    /// });
+    /// buffer.seal(&system);
+    /// assert!(buffer.is_sealed());
+    /// system.set(&other, 1);
+    /// assert_eq!(system.get(&buffer).len(), 8);
+    /// ```
+    pub fn seal(&self, system: &System) {
+        self.get_value(system);
+        #[cfg(feature = "trace")]
+        if cfg!(debug_assertions) {
+            let deps = self.trace.deps().into_iter().map(|(node_id, _)| node_id);
+            system.sealed_deps.lock().unwrap().extend(deps);
+        }
+        self.sealed.store(true, Ordering::Release);
+        if let Some(record) = &self.record {
+            record.seal();
+        }
+    }
+
+    /// Whether [`SyncVal::seal`] was called, or `System::optimize` of the `trace` feature sealed
+    /// this node.
+    pub fn is_sealed(&self) -> bool {
+        self.sealed.load(Ordering::Acquire)
+            || self
+                .record
+                .as_ref()
+                .is_some_and(|record| record.is_sealed())
+    }
+
+    /// The dependency with the newest version when the value was last stored,
+    /// `None` if it read no dependency.
+    #[cfg(feature = "trace")]
+    pub fn last_trigger(&self) -> Option<NodeId> {
+        self.trace.last_trigger()
+    }
+
+    /// The system version of the last read of this node, `None` if it was never read.
+    #[cfg(feature = "access")]
+    pub fn last_read_version(&self) -> Option<SystemVersion> {
+        self.access.last_read_version()
+    }
 }
-unsafe impl<T: Sync + Send, F: Send> Sync for SyncVal<T, F> {}
+unsafe impl<T: Sync + Send, F: Send, L: Sync> Sync for SyncVal<T, F, L> {}
 
-impl<T, F: FnMut(Update<T>)> SystemNode for SyncVal<T, F> {
+impl<T, F: FnMut(Update<T>), L: ValLock> SystemNode for SyncVal<T, F, L> {
     type Value = T;
 
+    fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    fn system_id(&self) -> SystemId {
+        self.system_id
+    }
+
     fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
+        match self.try_get_value(system) {
+            Some(value) => value,
+            None => LazyCatchError::Aborted.raise(),
+        }
+    }
+
+    fn try_get_value<'s>(&'s self, system: &'s System) -> Option<(SystemVersion, &'s Self::Value)> {
         self.system_id.check_system(system);
-        if self.check_version.get() != Some(system.version()) {
-            let lock = self
-                .lock
-                .try_lock()
-                .expect("Val update recursion or poison");
-            if self.check_version.get() != Some(system.version()) {
+        system.record_get();
+        self.access.record(system.version());
+        if !self.is_sealed() && self.check_version.get() != Some(system.version()) {
+            let lock = match self.lock.try_lock() {
+                Ok(lock) => lock,
+                Err(LazyCatchError::Recursion) if !is_updating(self.node_id) => {
+                    match self.lock.lock() {
+                        Ok(lock) => lock,
+                        Err(err) => err.raise(),
+                    }
+                }
+                Err(err) => err.raise(),
+            };
+            if self.check_version.get() != Some(system.version())
+                && !(system.is_frozen() && unsafe { &*self.value.get() }.1.is_some())
+            {
+                if observer::is_dispatching() {
+                    drop(lock);
+                    LazyCatchError::ObserverUpdate.raise();
+                }
                 let (update_fn, value) = unsafe { &mut *self.value.get() };
-                update_fn(Update::new(system, value.as_ref().map(|&(v, _)| v), value));
-                self.check_version.set(Some(system.version()));
+                let updating = Updating::enter(self.node_id);
+                let checked =
+                    run_update(system, self.node_id, value, false, &self.trace, update_fn);
+                drop(updating);
+                if checked {
+                    self.check_version.set(Some(system.version()));
+                    if let Some(record) = &self.record {
+                        record.check_version.set(Some(system.version()));
+                        #[cfg(feature = "trace")]
+                        record.set_deps(&self.trace);
+                        if let (Some(dump), Some((_, value))) = (self.dump, value) {
+                            record.set_debug(dump(value));
+                        }
+                    }
+                }
             }
             drop(lock);
         }
-        let (version, value) = unsafe { &*self.value.get() }.1.as_ref().unwrap();
-        (*version, value)
+        let (version, value) = unsafe { &*self.value.get() }.1.as_ref()?;
+        Some((*version, value))
+    }
+
+    fn is_computed(&self) -> bool {
+        self.check_version.get().is_some()
+    }
+
+    fn is_current(&self, system: &System) -> bool {
+        self.is_sealed() || self.check_version.get() == Some(system.version())
+    }
+
+    #[cfg(feature = "trace")]
+    fn last_deps(&self) -> Vec<(NodeId, SystemVersion)> {
+        self.trace.deps()
+    }
+}
+
+impl System {
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn thread_bound_val<T, F: FnMut(Update<T>)>(&self, f: F) -> ThreadBoundVal<T, F> {
+        ThreadBoundVal::new(self, f)
+    }
+}
+
+/// A [`Val`] that is `Sync` for any value, but can only be read on the thread that created it.
+///
+/// Any use other than its ids from another thread panics before touching the value or its
+/// state, so neither is ever shared.
+/// Sharing the node is sound for that reason, but it is only `Send` when `Val` is,
+/// since dropping a value such as an `Rc` on another thread would not be.
+///
+/// ```
+/// # use std::rc::Rc;
+/// # use lazy_catch::{System, Update};
+/// fn assert_sync<N: Sync>(_: &N) {}
+///
+/// let system = System::new();
+/// let a = system.thread_bound_val(|u: Update<Rc<i32>>| u.update(|| Rc::new(1)));
+/// assert_sync(&a);
+/// assert_eq!(**system.get(&a), 1);
+/// std::thread::scope(|s| {
+///     let result = s.spawn(|| *system.get(&a).clone()).join();
+///     assert!(result.is_err());
+/// });
+/// ```
+pub struct ThreadBoundVal<T, F = fn(Update<T>)> {
+    thread: ThreadId,
+    inner: Val<T, F>,
+}
+
+unsafe impl<T, F> Sync for ThreadBoundVal<T, F> {}
+
+impl<T, F: FnMut(Update<T>)> ThreadBoundVal<T, F> {
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn new(system: &System, f: F) -> Self {
+        Self {
+            thread: std::thread::current().id(),
+            inner: Val::new(system, f),
+        }
+    }
+
+    /// The dependency with the newest version when the value was last stored,
+    /// `None` if it read no dependency.
+    #[cfg(feature = "trace")]
+    pub fn last_trigger(&self) -> Option<NodeId> {
+        self.check_thread();
+        self.inner.last_trigger()
+    }
+
+    fn check_thread(&self) {
+        assert_eq!(
+            std::thread::current().id(),
+            self.thread,
+            "ThreadBoundVal used from another thread"
+        );
+    }
+}
+
+impl<T, F: FnMut(Update<T>)> SystemNode for ThreadBoundVal<T, F> {
+    type Value = T;
+
+    fn node_id(&self) -> NodeId {
+        self.inner.node_id()
+    }
+
+    fn system_id(&self) -> SystemId {
+        self.inner.system_id()
+    }
+
+    fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
+        self.check_thread();
+        self.inner.get_value(system)
+    }
+
+    fn try_get_value<'s>(&'s self, system: &'s System) -> Option<(SystemVersion, &'s Self::Value)> {
+        self.check_thread();
+        self.inner.try_get_value(system)
+    }
+
+    fn is_computed(&self) -> bool {
+        self.check_thread();
+        self.inner.is_computed()
+    }
+
+    fn is_current(&self, system: &System) -> bool {
+        self.check_thread();
+        self.inner.is_current(system)
+    }
+
+    #[cfg(feature = "trace")]
+    fn last_deps(&self) -> Vec<(NodeId, SystemVersion)> {
+        self.check_thread();
+        self.inner.last_deps()
+    }
+
+    fn tag(&self) -> Option<Tag> {
+        self.check_thread();
+        self.inner.tag()
+    }
+}
+
+impl System {
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn dyn_val<U: ?Sized, F: FnMut(Update<Box<U>>)>(&self, f: F) -> DynVal<U, F> {
+        DynVal::new(self, f)
+    }
+}
+
+/// A [`Val`] storing a boxed trait object, read as the unsized value behind the box.
+///
+/// Each update may box a different concrete type.
+///
+/// ```
+/// # use std::fmt::Display;
+/// # use lazy_catch::{System, Update};
+/// let mut system = System::new();
+/// let x = system.var(1);
+/// let shown = system.dyn_val(|mut u: Update<Box<dyn Display>>| {
+///     let x = *u.get(&x);
+///     u.update(|| if x > 0 { Box::new(x) } else { Box::new("none") });
+/// });
+/// let value: &dyn Display = system.get(&shown);
+/// assert_eq!(value.to_string(), "1");
+/// system.set(&x, 0);
+/// assert_eq!(system.get(&shown).to_string(), "none");
+/// ```
+pub struct DynVal<U: ?Sized, F = fn(Update<Box<U>>)> {
+    inner: Val<Box<U>, F>,
+}
+
+impl<U: ?Sized, F: FnMut(Update<Box<U>>)> DynVal<U, F> {
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn new(system: &System, f: F) -> Self {
+        Self {
+            inner: Val::new(system, f),
+        }
+    }
+
+    /// The system version when this node was created.
+    pub fn created_version(&self) -> SystemVersion {
+        self.inner.created_version()
+    }
+
+    /// The dependency with the newest version when the value was last stored,
+    /// `None` if it read no dependency.
+    #[cfg(feature = "trace")]
+    pub fn last_trigger(&self) -> Option<NodeId> {
+        self.inner.last_trigger()
+    }
+}
+
+impl<U: ?Sized, F: FnMut(Update<Box<U>>)> SystemNode for DynVal<U, F> {
+    type Value = U;
+
+    fn node_id(&self) -> NodeId {
+        self.inner.node_id()
+    }
+
+    fn system_id(&self) -> SystemId {
+        self.inner.system_id()
+    }
+
+    fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
+        let (version, value) = self.inner.get_value(system);
+        (version, &**value)
+    }
+
+    fn try_get_value<'s>(&'s self, system: &'s System) -> Option<(SystemVersion, &'s Self::Value)> {
+        let (version, value) = self.inner.try_get_value(system)?;
+        Some((version, &**value))
+    }
+
+    fn is_computed(&self) -> bool {
+        self.inner.is_computed()
+    }
+
+    fn is_current(&self, system: &System) -> bool {
+        self.inner.is_current(system)
+    }
+
+    #[cfg(feature = "trace")]
+    fn last_deps(&self) -> Vec<(NodeId, SystemVersion)> {
+        self.inner.last_deps()
+    }
+
+    fn tag(&self) -> Option<Tag> {
+        self.inner.tag()
     }
 }
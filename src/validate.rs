@@ -0,0 +1,119 @@
+use crate::var::Var;
+use crate::{System, SystemModify};
+
+impl System {
+    /// Apply a modify, then keep it only if `validate` accepts the resulting state.
+    ///
+    /// `apply` edits vars through a [`Tentative`], which keeps a clone of each var it touches.
+    /// `validate` runs at the tentative version, so the nodes it reads are computed from the
+    /// proposed values. On `Err` the touched vars get their old values back in a second modify.
+    ///
+    /// A rejected change still advances the version twice, and the restored vars are stamped
+    /// with the new version: nodes computed from the tentative state must see a change to
+    /// compute again. They end up with values equal to the ones before the call.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let width = system.var(4);
+    /// let height = system.var(3);
+    /// let area = system.val(|mut u: Update<i32>| {
+    ///     let (w, h) = (*u.get(&width), *u.get(&height));
+    ///     u.update(|| w * h);
+    /// });
+    /// let small = |system: &System| match *system.get(&area) {
+    ///     area if area <= 20 => Ok(()),
+    ///     area => Err(area),
+    /// };
+    ///
+    /// let result = system.modify_validated(|t| t.set(&width, 5), small);
+    /// assert_eq!(result, Ok(()));
+    /// assert_eq!(*system.get(&area), 15);
+    ///
+    /// let result = system.modify_validated(
+    ///     |t| {
+    ///         t.set(&width, 6);
+    ///         *t.var(&height) += 1;
+    ///     },
+    ///     small,
+    /// );
+    /// assert_eq!(result, Err(24));
+    /// assert_eq!((*system.get(&width), *system.get(&height)), (5, 3));
+    /// assert_eq!(*system.get(&area), 15);
+    /// ```
+    ///
+    /// Callbacks of [`System::on_commit`] are called once for an accepted change, after
+    /// `validate`, and not at all for a rejected one or its rollback.
+    ///
+    /// ```
+    /// # use std::sync::{Arc, Mutex};
+    /// # use lazy_catch::System;
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let commits = Arc::new(Mutex::new(Vec::new()));
+    /// system.on_commit({
+    ///     let commits = commits.clone();
+    ///     move |version| commits.lock().unwrap().push(version)
+    /// });
+    /// let positive = |system: &System| if *system.get(&x) > 0 { Ok(()) } else { Err(()) };
+    ///
+    /// assert_eq!(system.modify_validated(|t| t.set(&x, -1), positive), Err(()));
+    /// assert!(commits.lock().unwrap().is_empty());
+    /// assert_eq!(system.modify_validated(|t| t.set(&x, 2), positive), Ok(()));
+    /// assert_eq!(*commits.lock().unwrap(), [system.version()]);
+    /// ```
+    pub fn modify_validated<'v, E>(
+        &mut self,
+        apply: impl FnOnce(&mut Tentative<'_, 'v>),
+        validate: impl FnOnce(&System) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let mut tentative = Tentative {
+            modify: self.modify(),
+            undo: Vec::new(),
+        };
+        apply(&mut tentative);
+        let undo = std::mem::take(&mut tentative.undo);
+        let written = tentative.modify.take_written();
+        drop(tentative);
+        match validate(self) {
+            Ok(()) => {
+                if written {
+                    let version = self.version;
+                    self.commit_callbacks.call(version);
+                }
+                Ok(())
+            }
+            Err(err) => {
+                let mut modify = self.modify();
+                for undo in undo.into_iter().rev() {
+                    undo(&mut modify);
+                }
+                modify.take_written();
+                Err(err)
+            }
+        }
+    }
+}
+
+type Undo<'v> = Box<dyn FnOnce(&mut SystemModify) + 'v>;
+
+/// The modify of [`System::modify_validated`], remembering the old value of each var it edits.
+pub struct Tentative<'s, 'v> {
+    modify: SystemModify<'s>,
+    undo: Vec<Undo<'v>>,
+}
+
+impl<'v> Tentative<'_, 'v> {
+    /// Edit a var in place, the same as [`Var::modify`].
+    pub fn var<T: Clone>(&mut self, var: &'v Var<T>) -> &mut T {
+        let old = var.modify(&mut self.modify).clone();
+        self.undo
+            .push(Box::new(move |modify| *var.modify(modify) = old));
+        var.modify(&mut self.modify)
+    }
+
+    /// Set a var, the same as [`System::set`].
+    pub fn set<T: Clone>(&mut self, var: &'v Var<T>, value: T) {
+        *self.var(var) = value;
+    }
+}
@@ -1,15 +1,80 @@
 use std::cell::UnsafeCell;
+use std::ops::Deref;
 
-use crate::{System, SystemId, SystemModify, SystemNode, SystemVersion};
+use crate::history::HistoryVar;
+use crate::{NodeId, System, SystemId, SystemModify, SystemNode, SystemVersion};
 
 impl System {
+    #[must_use = "nodes must be stored to be read later"]
     pub fn var<T>(&self, value: T) -> Var<T> {
         Var::new(self, value)
     }
+
+    /// Set a single var in its own modify.
+    ///
+    /// ```
+    /// # use lazy_catch::System;
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// system.set(&x, 2);
+    /// assert_eq!(*system.get(&x), 2);
+    /// system.modify_var(&x, |x| *x += 1);
+    /// assert_eq!(*system.get(&x), 3);
+    /// ```
+    pub fn set<T>(&mut self, var: &Var<T>, value: T) {
+        *var.modify(&mut self.modify()) = value;
+    }
+
+    /// Change a single var in place in its own modify.
+    pub fn modify_var<T: ?Sized, R, F: FnOnce(&mut T) -> R>(&mut self, var: &Var<T>, f: F) -> R {
+        f(var.modify(&mut self.modify()))
+    }
+
+    /// Read `source` and set a clone of its value into `target` in its own modify,
+    /// keeping no link between them.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let x = system.var(2);
+    /// let square = system.val(|mut u: Update<i32>| {
+    ///     let x = *u.get(&x);
+    ///     u.update(|| x * x);
+    /// });
+    /// let saved = system.var(0_i64);
+    /// system.snapshot_into(&square, &saved);
+    /// system.set(&x, 3);
+    /// assert_eq!(*system.get(&square), 9);
+    /// assert_eq!(*system.get(&saved), 4);
+    /// ```
+    pub fn snapshot_into<N: SystemNode + ?Sized, T: From<N::Value>>(
+        &mut self,
+        source: &N,
+        target: &Var<T>,
+    ) where
+        N::Value: Clone,
+    {
+        let value = self.get(source).clone();
+        self.set(target, value.into());
+    }
+
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn var_array<T, const N: usize>(&self, values: [T; N]) -> [Var<T>; N] {
+        values.map(|value| self.var(value))
+    }
+
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn var_slice<T>(&self, values: impl IntoIterator<Item = T>) -> VarSlice<T> {
+        VarSlice {
+            vars: values.into_iter().map(|value| self.var(value)).collect(),
+        }
+    }
 }
 
 pub struct Var<T: ?Sized> {
     system_id: SystemId,
+    node_id: NodeId,
+    created_version: SystemVersion,
     value: UnsafeCell<(SystemVersion, T)>,
 }
 
@@ -23,29 +88,255 @@ impl<T> Var<T> {
     /// let system = System::new();
     /// let x = Var::new(&system, 0);
     /// ```
+    #[must_use = "nodes must be stored to be read later"]
     pub fn new(system: &System, value: T) -> Self {
         Self {
             system_id: system.id(),
+            node_id: NodeId::new(),
+            created_version: system.version(),
             value: UnsafeCell::new((system.version(), value)),
         }
     }
 }
 
 impl<T: ?Sized> Var<T> {
+    /// The system version when this node was created.
+    ///
+    /// ```
+    /// # use lazy_catch::System;
+    /// let mut system = System::new();
+    /// let created = system.version();
+    /// let x = system.var(0);
+    /// *x.modify(&mut system.modify()) = 1;
+    /// assert_eq!(x.created_version(), created);
+    /// ```
+    pub fn created_version(&self) -> SystemVersion {
+        self.created_version
+    }
+
+    /// A handle that reads this var but can not modify it, to share the value without the
+    /// capability to change it.
+    ///
+    /// The reader is the same node as the var, with the same id and versions.
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use lazy_catch::var::VarReader;
+    /// # use lazy_catch::{System, SystemNode, Update};
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let reader: Arc<VarReader<i32>> = Arc::new(x.reader());
+    /// let double = system.val(|mut u: Update<i32>| {
+    ///     let x = *u.get(&reader);
+    ///     u.update(|| x * 2);
+    /// });
+    /// assert_eq!(reader.node_id(), x.node_id());
+    /// assert_eq!(*system.get(&double), 2);
+    /// system.set(&x, 4);
+    /// assert_eq!(*system.get(&double), 8);
+    /// assert_eq!(system.get_versioned(&*reader), system.get_versioned(&x));
+    /// ```
+    pub fn reader(&self) -> VarReader<'_, T> {
+        VarReader { var: self }
+    }
+
     pub fn modify<'s>(&'s self, modify: &'s mut SystemModify) -> &'s mut T {
         self.system_id.check_modify(modify);
         let (version, value) = unsafe { &mut *self.value.get() };
-        *version = modify.version();
+        *version = modify.write_version(self.node_id);
         value
     }
 }
 
+impl<T: PartialEq> Var<T> {
+    /// Set the value only if it differs, returning whether it changed.
+    ///
+    /// An equal value keeps the old version, so nodes reading this var see no change.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, SystemNode};
+    /// let mut system = System::new();
+    /// let x = system.var(1);
+    /// let version = x.get_value(&system).0;
+    /// assert!(!x.set_if_changed(&mut system.modify(), 1));
+    /// assert_eq!(x.get_value(&system).0, version);
+    /// assert!(x.set_if_changed(&mut system.modify(), 2));
+    /// assert_eq!(x.get_value(&system), (system.version(), &2));
+    /// ```
+    pub fn set_if_changed(&self, modify: &mut SystemModify, value: T) -> bool {
+        self.system_id.check_modify(modify);
+        let (version, old) = unsafe { &mut *self.value.get() };
+        if *old == value {
+            return false;
+        }
+        *version = modify.write_version(self.node_id);
+        *old = value;
+        true
+    }
+}
+
+impl<T: Clone> Var<T> {
+    /// Edit the value in place, restoring it if `f` panics.
+    ///
+    /// The value is cloned before `f` runs, and the version is only stamped once `f` returned,
+    /// so a panic leaves the var as it was. Values that are not `Clone` can only be edited
+    /// with [`Var::modify`], which keeps a partial edit.
+    ///
+    /// ```
+    /// # use std::panic::{catch_unwind, AssertUnwindSafe};
+    /// # use lazy_catch::{System, SystemNode};
+    /// let mut system = System::new();
+    /// let x = system.var(vec![1, 2]);
+    /// let version = x.get_value(&system).0;
+    /// catch_unwind(AssertUnwindSafe(|| {
+    ///     x.modify_checked(&mut system.modify(), |x| {
+    ///         x.push(3);
+    ///         panic!("invalid edit");
+    ///     })
+    /// }))
+    /// .unwrap_err();
+    /// assert_eq!(x.get_value(&system), (version, &vec![1, 2]));
+    /// x.modify_checked(&mut system.modify(), |x| x.push(3));
+    /// assert_eq!(x.get_value(&system), (system.version(), &vec![1, 2, 3]));
+    /// ```
+    pub fn modify_checked<R, F: FnOnce(&mut T) -> R>(&self, modify: &mut SystemModify, f: F) -> R {
+        struct Rollback<'a, T> {
+            value: &'a mut T,
+            snapshot: Option<T>,
+        }
+
+        impl<T> Drop for Rollback<'_, T> {
+            fn drop(&mut self) {
+                if let Some(snapshot) = self.snapshot.take() {
+                    *self.value = snapshot;
+                }
+            }
+        }
+
+        self.system_id.check_modify(modify);
+        let (version, value) = unsafe { &mut *self.value.get() };
+        let snapshot = Some(value.clone());
+        let mut rollback = Rollback { value, snapshot };
+        let result = f(rollback.value);
+        rollback.snapshot = None;
+        *version = modify.write_version(self.node_id);
+        result
+    }
+}
+
 impl<T: ?Sized> SystemNode for Var<T> {
     type Value = T;
 
+    fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    fn system_id(&self) -> SystemId {
+        self.system_id
+    }
+
     fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
         self.system_id.check_system(system);
         let (version, value) = unsafe { &*self.value.get() };
         (*version, value)
     }
 }
+
+/// A read only handle of a [`Var`], see [`Var::reader`].
+pub struct VarReader<'v, T: ?Sized> {
+    var: &'v Var<T>,
+}
+
+impl<T: ?Sized> Clone for VarReader<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for VarReader<'_, T> {}
+
+impl<T: ?Sized> SystemNode for VarReader<'_, T> {
+    type Value = T;
+
+    fn node_id(&self) -> NodeId {
+        self.var.node_id()
+    }
+
+    fn system_id(&self) -> SystemId {
+        self.var.system_id()
+    }
+
+    fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
+        self.var.get_value(system)
+    }
+
+    fn is_computed(&self) -> bool {
+        self.var.is_computed()
+    }
+
+    fn is_current(&self, system: &System) -> bool {
+        self.var.is_current(system)
+    }
+}
+
+/// Build a source node from a value, for generic code that abstracts over node construction.
+///
+/// Implemented for every sized type, so `impl ToNode` accepts any value.
+///
+/// ```
+/// # use lazy_catch::System;
+/// # use lazy_catch::var::{ToNode, Var};
+/// fn source<T: ToNode>(system: &System, value: T) -> Var<T> {
+///     value.into_var(system)
+/// }
+///
+/// let system = System::new();
+/// let x = source(&system, 3);
+/// let name = "x".to_owned().into_var(&system);
+/// assert_eq!(*system.get(&x), 3);
+/// assert_eq!(system.get(&name), "x");
+/// ```
+pub trait ToNode: Sized {
+    /// Move the value into a new var, same as [`System::var`].
+    #[must_use = "nodes must be stored to be read later"]
+    fn into_var(self, system: &System) -> Var<Self> {
+        system.var(self)
+    }
+
+    /// Move the value into a new history var, same as [`System::history_var`].
+    #[must_use = "nodes must be stored to be read later"]
+    fn into_history_var(self, system: &System, depth: usize) -> HistoryVar<Self> {
+        system.history_var(self, depth)
+    }
+}
+
+impl<T> ToNode for T {}
+
+/// Contiguous vars, each with its own version.
+///
+/// ```
+/// # use lazy_catch::{System, SystemNode};
+/// let mut system = System::new();
+/// let sliders = system.var_slice([0; 4]);
+/// let before = sliders[1].get_value(&system).0;
+/// *sliders.modify(0, &mut system.modify()) = 5;
+/// assert_eq!(*system.get(&sliders[0]), 5);
+/// assert_eq!(sliders[1].get_value(&system).0, before);
+/// ```
+pub struct VarSlice<T> {
+    vars: Box<[Var<T>]>,
+}
+
+impl<T> VarSlice<T> {
+    pub fn modify<'s>(&'s self, index: usize, modify: &'s mut SystemModify) -> &'s mut T {
+        self.vars[index].modify(modify)
+    }
+}
+
+impl<T> Deref for VarSlice<T> {
+    type Target = [Var<T>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.vars
+    }
+}
@@ -0,0 +1,72 @@
+use std::cell::UnsafeCell;
+
+use crate::{NodeId, System, SystemId, SystemNode, SystemVersion};
+
+impl System {
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn version_node(&self) -> VersionNode {
+        VersionNode::new(self)
+    }
+}
+
+/// A node whose value is the current system version, so it changes at every modify.
+///
+/// ```
+/// # use lazy_catch::{System, Update};
+/// let mut system = System::new();
+/// let tick = system.version_node();
+/// let frames = system.val(|mut u: Update<u64>| {
+///     u.get(&tick);
+///     u.update_with_old(|old| old.map_or(0, |frame| frame + 1));
+/// });
+/// assert_eq!(*system.get(&tick), system.version());
+/// assert_eq!(*system.get(&frames), 0);
+/// system.modify();
+/// system.modify();
+/// assert_eq!(*system.get(&frames), 1);
+/// assert_eq!(*system.get(&tick), system.version());
+/// ```
+pub struct VersionNode {
+    system_id: SystemId,
+    node_id: NodeId,
+    created_version: SystemVersion,
+    value: UnsafeCell<SystemVersion>,
+}
+
+impl VersionNode {
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn new(system: &System) -> Self {
+        Self {
+            system_id: system.id(),
+            node_id: NodeId::new(),
+            created_version: system.version(),
+            value: UnsafeCell::new(system.version()),
+        }
+    }
+
+    /// The system version when this node was created.
+    pub fn created_version(&self) -> SystemVersion {
+        self.created_version
+    }
+}
+
+impl SystemNode for VersionNode {
+    type Value = SystemVersion;
+
+    fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    fn system_id(&self) -> SystemId {
+        self.system_id
+    }
+
+    fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
+        self.system_id.check_system(system);
+        // Values handed out at an older version can not be alive, a modify borrowed the system.
+        if unsafe { *self.value.get() } != system.version() {
+            unsafe { *self.value.get() = system.version() };
+        }
+        (system.version(), unsafe { &*self.value.get() })
+    }
+}
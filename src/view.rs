@@ -0,0 +1,247 @@
+use std::cell::UnsafeCell;
+use std::marker::PhantomData;
+
+use crate::{NodeId, System, SystemId, SystemNode, SystemVersion, Tag};
+
+/// Views over a node that read it as another type without caching.
+pub trait NodeView: SystemNode {
+    /// Read the value through `AsRef<U>`, forwarding the version.
+    ///
+    /// ```
+    /// # use lazy_catch::view::NodeView;
+    /// # use lazy_catch::{System, SystemNode};
+    /// let system = System::new();
+    /// let name = system.var(String::from("lazy"));
+    /// let view = name.as_ref_node::<str>();
+    /// assert_eq!(system.get(&view), "lazy");
+    /// assert_eq!(view.node_id(), name.node_id());
+    /// ```
+    #[must_use = "nodes must be stored to be read later"]
+    fn as_ref_node<U: ?Sized>(&self) -> AsRefNode<'_, Self, U>
+    where
+        Self::Value: AsRef<U>,
+    {
+        AsRefNode {
+            node: self,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Read a part of the value borrowed by `project`, forwarding the version.
+    ///
+    /// `project` must return a borrow of its argument, or of data outliving the node,
+    /// so it can not build a new value. It runs at every read.
+    ///
+    /// ```
+    /// # use lazy_catch::view::NodeView;
+    /// # use lazy_catch::System;
+    /// struct Person {
+    ///     name: String,
+    ///     age: u32,
+    /// }
+    ///
+    /// let mut system = System::new();
+    /// let person = system.var(Person { name: String::from("Ada"), age: 36 });
+    /// let name = person.project(|person: &Person| person.name.as_str());
+    /// assert_eq!(system.get(&name), "Ada");
+    /// system.modify_var(&person, |person| person.name.push('!'));
+    /// assert_eq!(system.get(&name), "Ada!");
+    /// ```
+    #[must_use = "nodes must be stored to be read later"]
+    fn project<U: ?Sized, P: Fn(&Self::Value) -> &U>(&self, project: P) -> Project<'_, Self, U, P> {
+        Project {
+            node: self,
+            project,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<N: SystemNode + ?Sized> NodeView for N {}
+
+/// See [`NodeView::as_ref_node`].
+pub struct AsRefNode<'n, N: ?Sized, U: ?Sized> {
+    node: &'n N,
+    _marker: PhantomData<fn(&U)>,
+}
+
+impl<'n, N: SystemNode + ?Sized, U: ?Sized> SystemNode for AsRefNode<'n, N, U>
+where
+    N::Value: AsRef<U>,
+{
+    type Value = U;
+
+    fn node_id(&self) -> NodeId {
+        self.node.node_id()
+    }
+
+    fn system_id(&self) -> SystemId {
+        self.node.system_id()
+    }
+
+    fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
+        let (version, value) = self.node.get_value(system);
+        (version, value.as_ref())
+    }
+
+    fn try_get_value<'s>(&'s self, system: &'s System) -> Option<(SystemVersion, &'s Self::Value)> {
+        let (version, value) = self.node.try_get_value(system)?;
+        Some((version, value.as_ref()))
+    }
+
+    fn is_computed(&self) -> bool {
+        self.node.is_computed()
+    }
+
+    fn is_current(&self, system: &System) -> bool {
+        self.node.is_current(system)
+    }
+
+    #[cfg(feature = "trace")]
+    fn last_deps(&self) -> Vec<(NodeId, SystemVersion)> {
+        self.node.last_deps()
+    }
+
+    fn tag(&self) -> Option<Tag> {
+        self.node.tag()
+    }
+}
+
+/// See [`NodeView::project`].
+pub struct Project<'n, N: ?Sized, U: ?Sized, P> {
+    node: &'n N,
+    project: P,
+    _marker: PhantomData<fn(&U)>,
+}
+
+impl<'n, N: SystemNode + ?Sized, U: ?Sized, P: Fn(&N::Value) -> &U> SystemNode
+    for Project<'n, N, U, P>
+{
+    type Value = U;
+
+    fn node_id(&self) -> NodeId {
+        self.node.node_id()
+    }
+
+    fn system_id(&self) -> SystemId {
+        self.node.system_id()
+    }
+
+    fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
+        let (version, value) = self.node.get_value(system);
+        (version, (self.project)(value))
+    }
+
+    fn try_get_value<'s>(&'s self, system: &'s System) -> Option<(SystemVersion, &'s Self::Value)> {
+        let (version, value) = self.node.try_get_value(system)?;
+        Some((version, (self.project)(value)))
+    }
+
+    fn is_computed(&self) -> bool {
+        self.node.is_computed()
+    }
+
+    fn is_current(&self, system: &System) -> bool {
+        self.node.is_current(system)
+    }
+
+    #[cfg(feature = "trace")]
+    fn last_deps(&self) -> Vec<(NodeId, SystemVersion)> {
+        self.node.last_deps()
+    }
+
+    fn tag(&self) -> Option<Tag> {
+        self.node.tag()
+    }
+}
+
+impl System {
+    /// A node computing its value from the system with `f`, without tracking what `f` reads.
+    ///
+    /// `f` runs at the first read in each system version, and its version is always the
+    /// current one, so nodes reading it store a new value after every modify. This opts out
+    /// of laziness, it is meant for glue code that reads nodes in a way the graph can not follow.
+    ///
+    /// `f` returns an owned value: a reference returned from the system would have to outlive
+    /// every node `f` reads.
+    ///
+    /// ```
+    /// # use lazy_catch::{System, Update};
+    /// let mut system = System::new();
+    /// let names = system.var(vec![String::from("a"), String::from("b")]);
+    /// let selected = system.var(1);
+    /// let current = system.view(|system: &System| system.get(&names)[*system.get(&selected)].clone());
+    /// assert_eq!(system.get(&current), "b");
+    /// assert_eq!(system.get_versioned(&current).0, system.version());
+    /// let upper = system.val(|mut u: Update<String>| {
+    ///     let name = u.get(&current).to_uppercase();
+    ///     u.update(|| name);
+    /// });
+    /// system.set(&selected, 0);
+    /// assert_eq!(system.get(&upper), "A");
+    /// ```
+    #[must_use = "nodes must be stored to be read later"]
+    pub fn view<T, F: Fn(&System) -> T>(&self, f: F) -> FnView<T, F> {
+        FnView {
+            system_id: self.id(),
+            node_id: NodeId::new(),
+            created_version: self.version(),
+            f,
+            value: UnsafeCell::new(None),
+        }
+    }
+}
+
+/// See [`System::view`].
+pub struct FnView<T, F> {
+    system_id: SystemId,
+    node_id: NodeId,
+    created_version: SystemVersion,
+    f: F,
+    value: UnsafeCell<Option<(SystemVersion, T)>>,
+}
+
+impl<T, F> FnView<T, F> {
+    /// The system version when this node was created.
+    pub fn created_version(&self) -> SystemVersion {
+        self.created_version
+    }
+}
+
+impl<T, F: Fn(&System) -> T> SystemNode for FnView<T, F> {
+    type Value = T;
+
+    fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    fn system_id(&self) -> SystemId {
+        self.system_id
+    }
+
+    fn get_value<'s>(&'s self, system: &'s System) -> (SystemVersion, &'s Self::Value) {
+        self.system_id.check_system(system);
+        if unsafe { &*self.value.get() }
+            .as_ref()
+            .map(|&(version, _)| version)
+            != Some(system.version())
+        {
+            let value = (self.f)(system);
+            // Values handed out at an older version can not be alive, a modify borrowed the system.
+            unsafe { *self.value.get() = Some((system.version(), value)) };
+        }
+        let (version, value) = unsafe { &*self.value.get() }.as_ref().unwrap();
+        (*version, value)
+    }
+
+    fn is_computed(&self) -> bool {
+        unsafe { &*self.value.get() }.is_some()
+    }
+
+    fn is_current(&self, system: &System) -> bool {
+        unsafe { &*self.value.get() }
+            .as_ref()
+            .map(|&(version, _)| version)
+            == Some(system.version())
+    }
+}